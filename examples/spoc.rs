@@ -45,13 +45,13 @@ mod tests {
     #[ignore]
     #[tokio::test]
     async fn test_spoc_upload_with_progress() {
-        use buaa_api::api::spoc::{SpocApi, UploadArgs, UploadProgress, UploadRes};
+        use buaa_api::api::spoc::{UploadArgs, UploadProgress, UploadRes};
         use tokio::sync::{mpsc, oneshot};
 
         use std::fs::File;
+        use std::sync::Arc;
 
-        let context = Context::with_auth("./data").unwrap();
-        let spoc = context.spoc();
+        let context = Arc::new(Context::with_auth("./data").unwrap());
 
         let file_path = "data/file.zip";
         let file_name = "file.zip";
@@ -62,13 +62,14 @@ mod tests {
         let (progress_tx, mut progress_rx) = mpsc::unbounded_channel::<UploadProgress>();
         let (result_tx, result_rx) = oneshot::channel::<buaa_api::Result<UploadRes>>();
 
-        let client = spoc.client().clone();
+        let ctx = Arc::clone(&context);
 
         let upload = async move {
-            SpocApi::upload_callback(&client, &args, reader, |progress| {
-                progress_tx.send(progress).unwrap();
-            })
-            .await
+            ctx.spoc()
+                .upload_callback(&args, reader, |progress| {
+                    progress_tx.send(progress).unwrap();
+                })
+                .await
         };
 
         tokio::spawn(async move {
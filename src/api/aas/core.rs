@@ -6,25 +6,41 @@ use crate::error::Error;
 
 impl super::AasApi {
     /// # Login to AasApi
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "aas.login",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn login(&self) -> crate::Result<()> {
-        let cred = self.cred.load();
-        if cred.is_expired::<Sso>() {
-            self.api::<Sso>().login().await?;
-        }
+        self.single_flight::<Aas>(self.login_once()).await
+    }
 
-        let login_url = "https://sso.buaa.edu.cn/login?service=https%3A%2F%2Fbyxt.buaa.edu.cn%2Fjwapp%2Fsys%2Fhomeapp%2Findex.do%3FcontextPath%3D%2Fjwapp";
+    async fn login_once(&self) -> crate::Result<()> {
         // 或者有 &ticket=xxx
-        let verify_url = "https://byxt.buaa.edu.cn/jwapp/sys/homeapp/index.do?contextPath=/jwapp";
-        let res = self.client.get(login_url).send().await?;
-        if !res.url().as_str().starts_with(verify_url) {
+        let verify_url = format!(
+            "{}/jwapp/sys/homeapp/index.do?contextPath=/jwapp",
+            self.endpoints.byxt
+        );
+        let res = self.api::<Sso>().service_login(&verify_url).await?;
+        if !res.final_url.as_str().starts_with(&verify_url) {
             return Err(Error::server("Login failed").with_label("Aas"));
         }
-        cred.refresh::<Sso>();
-        cred.refresh::<Aas>();
+        self.cred.update(|c| c.refresh::<Aas>());
         Ok(())
     }
 
     /// # Universal request for AasApi
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "aas.universal_request",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn universal_request<P>(
         &self,
         url: &str,
@@ -34,17 +50,23 @@ impl super::AasApi {
     where
         P: serde::Serialize + ?Sized,
     {
-        let cred = self.cred.load();
-        if cred.is_expired::<Aas>() {
-            self.login().await?;
-        }
+        let method = &method;
+        self.relogin_once::<Aas, _, _>(|| async move {
+            if self.cred.load().is_expired::<Aas>() {
+                self.login().await?;
+            }
 
-        let res = self
-            .client
-            .request(method, url)
-            .query(&payload)
-            .send()
-            .await?;
-        Ok(res.bytes().await?)
+            let res = self
+                .request(method.clone(), url)
+                .query(&payload)
+                .send()
+                .await?;
+            // 会话失效时转跳 SSO
+            if self.is_rejected(&res) {
+                return Err(Error::expired("Aas"));
+            }
+            Ok(res.bytes())
+        })
+        .await
     }
 }
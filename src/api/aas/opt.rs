@@ -6,38 +6,69 @@ use super::data::{Config, Res, Schedules};
 
 impl super::AasApi {
     /// # Get user config
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "aas.get_config",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn get_config(&self) -> crate::Result<Config> {
-        let url = "https://byxt.buaa.edu.cn/jwapp/sys/homeapp/api/home/currentUser.do";
-        let bytes = self.universal_request(url, Method::GET, &()).await?;
+        let url = format!(
+            "{}/jwapp/sys/homeapp/api/home/currentUser.do",
+            self.endpoints.byxt
+        );
+        let bytes = self.universal_request(&url, Method::GET, &()).await?;
         let config: Data<Config> = Res::parse(&bytes, "Failed to get config")?;
         Ok(config.0)
     }
 
     /// # Query week schedule
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "aas.query_week_schedule",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn query_week_schedule(&self, config: &Config) -> crate::Result<Schedules> {
-        let url =
-            "https://byxt.buaa.edu.cn/jwapp/sys/homeapp/api/home/student/getMyScheduleDetail.do";
+        let url = format!(
+            "{}/jwapp/sys/homeapp/api/home/student/getMyScheduleDetail.do",
+            self.endpoints.byxt
+        );
         let query = [
             ("termCode", config.term.as_str()),
             ("campusCode", ""),
             ("type", "week"),
             ("week", &config.week.to_string()),
         ];
-        let bytes = self.universal_request(url, Method::POST, &query).await?;
+        let bytes = self.universal_request(&url, Method::POST, &query).await?;
         let res: Schedules = Res::parse(&bytes, "Failed to get week schedule")?;
         Ok(res)
     }
 
     /// # Query term schedule
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "aas.query_term_schedule",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn query_term_schedule(&self, config: &Config) -> crate::Result<Schedules> {
-        let url =
-            "https://byxt.buaa.edu.cn/jwapp/sys/homeapp/api/home/student/getMyScheduleDetail.do";
+        let url = format!(
+            "{}/jwapp/sys/homeapp/api/home/student/getMyScheduleDetail.do",
+            self.endpoints.byxt
+        );
         let query = [
             ("termCode", config.term.as_str()),
             ("campusCode", ""),
             ("type", "term"),
         ];
-        let bytes = self.universal_request(url, Method::POST, &query).await?;
+        let bytes = self.universal_request(&url, Method::POST, &query).await?;
         let res: Schedules = Res::parse(&bytes, "Failed to get term schedule")?;
         Ok(res)
     }
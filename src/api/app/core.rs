@@ -1,8 +1,10 @@
 use bytes::Bytes;
+use reqwest::Url;
 use reqwest::header::USER_AGENT;
 
 use crate::api::{App, Sso};
 use crate::error::Error;
+use crate::utils;
 
 const APP_UA: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/132.0.0.0 Safari/537.36 MicroMessenger/7.0.20.1781(0x6700143B) NetType/WIFI MiniProgramEnv/Windows WindowsWechat/WMPF WindowsWechat(0x63090a13) UnifiedPCWindowsWechat(0xf2541022) XWEB/16467";
 
@@ -11,44 +13,69 @@ const APP_UA: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.
 
 impl super::AppApi {
     /// # Login to AppApi
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "app.login",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn login(&self) -> crate::Result<()> {
-        let cred = self.cred.load();
-        if cred.is_expired::<Sso>() {
+        self.single_flight::<App>(self.login_once()).await
+    }
+
+    async fn login_once(&self) -> crate::Result<()> {
+        if self.cred.load().is_expired::<Sso>() {
             self.api::<Sso>().login().await?;
         }
-        let login_url = "https://app.buaa.edu.cn/uc/wap/minigram/cas-login?login_from=xiaochengxu";
-        let verify_url = "https://app.buaa.edu.cn/uc/wap/minigram/cas-login?redirect=https%3A%2F%2Fapp.buaa.edu.cn%2Fsite%2Fcenter%2Fpersonal&login_from=xiaochengxu";
+        let base = &self.endpoints.app;
+        let login_url = format!("{base}/uc/wap/minigram/cas-login?login_from=xiaochengxu");
+        let redirect = utils::url_encode(&format!("{base}/site/center/personal"));
+        let verify_url =
+            format!("{base}/uc/wap/minigram/cas-login?redirect={redirect}&login_from=xiaochengxu");
         let res = self
-            .client
-            .get(login_url)
+            .get(&login_url)
             .header(USER_AGENT, APP_UA)
             .send()
             .await?;
         if res.url().as_str() != verify_url {
-            let text = res.text().await?;
+            let text = res.text();
             return Err(Error::server("Login failed")
                 .with_label("App")
                 .with_source(text));
         }
-        cred.refresh::<Sso>();
-        cred.refresh::<App>();
+        self.cred.update(|c| {
+            c.refresh::<Sso>();
+            c.refresh::<App>();
+        });
         Ok(())
     }
 
     /// # Universal Request for AppApi
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "app.universal_request",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn universal_request(&self, url: &str) -> crate::Result<Bytes> {
-        let cred = self.cred.load();
-        if cred.is_expired::<App>() {
-            self.login().await?;
-        }
+        self.relogin_once::<App, _, _>(|| async move {
+            if self.cred.load().is_expired::<App>() {
+                self.login().await?;
+            }
 
-        let res = self
-            .client
-            .get(url)
-            .header(USER_AGENT, APP_UA)
-            .send()
-            .await?;
+            let res = self.get(url).header(USER_AGENT, APP_UA).send().await?;
+            // 会话失效时经 SSO 转跳, 最终停在登录页或者应用首页而不是请求的页面
+            let redirected = Url::parse(url).is_ok_and(|u| u.path() != res.url().path());
+            if self.is_rejected(&res) || redirected {
+                return Err(Error::expired("App"));
+            }
 
-        Ok(res.bytes().await?)
+            Ok(res.bytes())
+        })
+        .await
     }
 }
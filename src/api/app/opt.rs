@@ -5,9 +5,17 @@ use super::Exams;
 
 impl super::AppApi {
     /// # Get exam schedule
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "app.get_exam",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn get_exam(&self) -> crate::Result<Exams> {
-        let url = "https://app.buaa.edu.cn/exam/wap/default/index";
-        let bytes = self.universal_request(url).await?;
+        let url = format!("{}/exam/wap/default/index", self.endpoints.app);
+        let bytes = self.universal_request(&url).await?;
 
         // JSON 硬嵌在 HTML 里是真没招了. 可能是个不稳定的解析, 依赖于服务端渲染的稳定度
         match utils::parse_by_tag(&bytes, "\n        data: ", ",\n")
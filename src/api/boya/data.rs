@@ -2,7 +2,7 @@ use serde::{Deserialize, Deserializer};
 use serde_json::Value;
 
 use crate::api::Data;
-use crate::error::Error;
+use crate::error::{Code, Error};
 use crate::utils::time::{DateTime, Month};
 
 #[derive(Deserialize)]
@@ -17,15 +17,33 @@ impl<'de, T: Deserialize<'de>> Res<T> {
         let res: Res<T> = serde_json::from_slice(&v)?;
         // 凭据过期 code 也是 200, 那你这 code 有什么用啊
         if res.status != "0" {
+            let code = error_code(&res.errmsg);
             let source = format!("Status Code: {}. Error Message: {}", res.status, res.errmsg);
-            return Err(Error::server("Operation failed")
+            let err = Error::server("Operation failed")
                 .with_label("Boya")
-                .with_source(source));
+                .with_source(source);
+            return Err(match code {
+                Some(code) => err.with_code(code),
+                None => err,
+            });
         }
         Ok(res.data)
     }
 }
 
+// 状态码都是 1, 只能从错误信息区分
+fn error_code(msg: &str) -> Option<Code> {
+    if msg.contains("已满") || msg.contains("余量不足") {
+        Some(Code::BoyaCourseFull)
+    } else if msg.contains("未开始") || msg.contains("未开放") {
+        Some(Code::BoyaNotOpen)
+    } else if msg.contains("冲突") {
+        Some(Code::BoyaTimeConflict)
+    } else {
+        None
+    }
+}
+
 // ====================
 // 用于 get_semester
 // ====================
@@ -1,3 +1,5 @@
+use serde::de::IgnoredAny;
+
 use crate::Error;
 use crate::api::Data;
 use crate::crypto::rand::{Rng, WyRng};
@@ -7,26 +9,43 @@ use super::data::{Coordinate, Course, Res, Selected, Semester, SignInfo, SignRes
 
 impl BoyaApi {
     /// # Get Current Semester
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "boya.get_semester",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn get_semester(&self) -> crate::Result<Semester> {
-        let url = "https://bykc.buaa.edu.cn/sscv/getAllConfig";
+        let url = format!("{}/sscv/getAllConfig", self.endpoints.bykc);
         let payload = serde_json::json!({});
-        let bytes = self.universal_request(url, &payload).await?;
+        let bytes = self.encrypted_request(&url, &payload, true).await?;
         let res: Data<Semester> = Res::parse(&bytes)?;
         Ok(res.0)
     }
 
     /// # Query Course List
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "boya.query_courses",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn query_courses(&self, page: u8, size: u8) -> crate::Result<Vec<Course>> {
-        // TODO: VPN 方法使用下面的 URL, 但我还没想好怎么分组
-        // https://d.buaa.edu.cn/https/77726476706e69737468656265737421f2ee4a9f69327d517f468ca88d1b203b/sscv/queryStudentSemesterCourseByPage
-        let url = "https://bykc.buaa.edu.cn/sscv/queryStudentSemesterCourseByPage";
+        let url = format!(
+            "{}/sscv/queryStudentSemesterCourseByPage",
+            self.endpoints.bykc
+        );
         // 说真的我从来没想过一页十个是不够的. 开放选课半个月后才真的上课, 学校是真**啊
         // 不过考虑到可以通过 `query_selected` 获取那些选了但是在这里看不到的课, 所以不推荐用这个分页
         let payload = serde_json::json!({
             "pageNumber": page,
             "pageSize": size,
         });
-        let bytes = self.universal_request(url, &payload).await?;
+        let bytes = self.encrypted_request(&url, &payload, true).await?;
         let res: Data<Vec<Course>> = Res::parse(&bytes)?;
         Ok(res.0)
     }
@@ -34,12 +53,20 @@ impl BoyaApi {
     /// # Query Single Course Info
     ///
     /// - Input: Course ID from [Course] via [BoyaApi::query_courses]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "boya.query_course",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn query_course(&self, id: u32) -> crate::Result<Course> {
-        let url = "https://bykc.buaa.edu.cn/sscv/queryCourseById";
+        let url = format!("{}/sscv/queryCourseById", self.endpoints.bykc);
         let payload = serde_json::json!({
             "id": id,
         });
-        let bytes = self.universal_request(url, &payload).await?;
+        let bytes = self.encrypted_request(&url, &payload, true).await?;
         let res: Course = Res::parse(&bytes)?;
         Ok(res)
     }
@@ -47,23 +74,39 @@ impl BoyaApi {
     /// # Query Selected Course List
     ///
     /// - Input: Semester from [Semester::estimated_current]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "boya.query_selected",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn query_selected(&self, semester: Semester) -> crate::Result<Vec<Selected>> {
-        let url = "https://bykc.buaa.edu.cn/sscv/queryChosenCourse";
+        let url = format!("{}/sscv/queryChosenCourse", self.endpoints.bykc);
         // 要求时间格式为 hh:mm:ss, to_string 方法会导致 秒 有小数点, 不过似乎不影响
         let payload = serde_json::json!({
             "startDate": semester.start.format(),
             "endDate": semester.end.format(),
         });
-        let bytes = self.universal_request(url, &payload).await?;
+        let bytes = self.encrypted_request(&url, &payload, true).await?;
         let res: Data<Vec<Selected>> = Res::parse(&bytes)?;
         Ok(res.0)
     }
 
     /// # Query Statistic
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "boya.query_statistic",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn query_statistic(&self) -> crate::Result<Statistic> {
-        let url = "https://bykc.buaa.edu.cn/sscv/queryStatisticByUserId";
+        let url = format!("{}/sscv/queryStatisticByUserId", self.endpoints.bykc);
         let payload = serde_json::json!({});
-        let bytes = self.universal_request(url, &payload).await?;
+        let bytes = self.encrypted_request(&url, &payload, true).await?;
         let res: Data<Statistic> = Res::parse(&bytes)?;
         Ok(res.0)
     }
@@ -71,33 +114,53 @@ impl BoyaApi {
     /// # Select Course
     ///
     /// - Input: Course ID from [Course] via [BoyaApi::query_course]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "boya.select_course",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn select_course(&self, id: u32) -> crate::Result<()> {
-        let url = "https://bykc.buaa.edu.cn/sscv/choseCourse";
+        let url = format!("{}/sscv/choseCourse", self.endpoints.bykc);
         let payload = serde_json::json!({
             "courseId": id,
         });
         // data 字段包含一个 courseCurrentCount 字段, 操作后的当前容量, 感觉没什么用
-        let _ = self.universal_request(url, &payload).await?;
+        // 但选满, 未开放等失败只体现在 status 上, 必须检查
+        let bytes = self.encrypted_request(&url, &payload, false).await?;
+        let _: IgnoredAny = Res::parse(&bytes)?;
         Ok(())
     }
 
     /// # Drop Course
     ///
     /// - Input: Course ID from [Course] via [BoyaApi::query_course] or [Selected] via [BoyaApi::query_selected]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "boya.drop_course",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn drop_course(&self, id: u32) -> crate::Result<()> {
-        let url = "https://bykc.buaa.edu.cn/sscv/delChosenCourse";
+        let url = format!("{}/sscv/delChosenCourse", self.endpoints.bykc);
         let payload = serde_json::json!({
             "id": id,
         });
         // data 字段包含一个 courseCurrentCount 字段, 操作后的当前容量, 感觉没什么用
-        let _ = self.universal_request(url, &payload).await?;
+        // 退课失败同样只体现在 status 上
+        let bytes = self.encrypted_request(&url, &payload, false).await?;
+        let _: IgnoredAny = Res::parse(&bytes)?;
         Ok(())
     }
 
     // 这个接口只在 Android UA 时才能找到, 但不妨碍使用
     /// # Sign Course (Internal)
     async fn sign_course(&self, id: u32, c: &Coordinate, t: u8) -> crate::Result<SignRes> {
-        let url = "https://bykc.buaa.edu.cn/sscv/signCourseByUser";
+        let url = format!("{}/sscv/signCourseByUser", self.endpoints.bykc);
         let mut rng = WyRng::new();
         let offset = 1e-5;
 
@@ -111,7 +174,7 @@ impl BoyaApi {
             "signLng": c.longitude + lng_offset,
             "signType": t,
         });
-        let bytes = self.universal_request(url, &payload).await?;
+        let bytes = self.encrypted_request(&url, &payload, false).await?;
         let res: Data<SignRes> = Res::parse(&bytes)?;
         Ok(res.0)
     }
@@ -121,6 +184,14 @@ impl BoyaApi {
     /// - Input:
     ///     - Course ID from [Course]
     ///     - Coordinate from [Course.sign_config.coordinate]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "boya.checkin_course",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn checkin_course(&self, id: u32, c: &Coordinate) -> crate::Result<SignInfo> {
         Ok(self.sign_course(id, c, 1).await?.checkin)
     }
@@ -130,6 +201,14 @@ impl BoyaApi {
     /// - Input:
     ///     - Course ID from [Course]
     ///     - Coordinate from [Course.sign_config.coordinate]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "boya.checkout_course",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn checkout_course(&self, id: u32, c: &Coordinate) -> crate::Result<SignInfo> {
         self.sign_course(id, c, 2)
             .await?
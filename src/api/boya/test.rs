@@ -0,0 +1,28 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::api::boya::data::Res;
+    use crate::error::Code;
+
+    fn code(errmsg: &str) -> Option<Code> {
+        let raw = format!(r#"{{"status":"1","errmsg":"{errmsg}","data":null}}"#);
+        Res::<Value>::parse(raw.as_bytes())
+            .unwrap_err()
+            .code()
+            .copied()
+    }
+
+    #[test]
+    fn test_error_code() {
+        assert_eq!(Some(Code::BoyaCourseFull), code("该课程人数已满"));
+        assert_eq!(Some(Code::BoyaCourseFull), code("课程余量不足"));
+        assert_eq!(Some(Code::BoyaNotOpen), code("选课未开始"));
+        assert_eq!(Some(Code::BoyaNotOpen), code("该课程暂未开放选课"));
+        assert_eq!(Some(Code::BoyaTimeConflict), code("与已选课程时间冲突"));
+        assert_eq!(None, code("系统繁忙"));
+
+        let raw = r#"{"status":"0","errmsg":"请求成功","data":1}"#;
+        assert_eq!(Value::from(1), Res::<Value>::parse(raw.as_bytes()).unwrap());
+    }
+}
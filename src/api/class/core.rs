@@ -11,14 +11,26 @@ const CLASS_DES_KEY: &[u8] = b"Jyd#351*";
 
 impl super::ClassApi {
     /// # Login to ClassApi
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "class.login",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn login(&self) -> crate::Result<()> {
+        self.single_flight::<Class>(self.login_once()).await
+    }
+
+    async fn login_once(&self) -> crate::Result<()> {
         if self.cred.load().is_expired::<Sso>() {
             self.api::<Sso>().login().await?;
         }
 
         // 获取 JSESSIONID
-        let url = "https://iclass.buaa.edu.cn:8346/";
-        let res = self.client.get(url).send().await?;
+        let url = format!("{}/", self.endpoints.iclass);
+        let res = self.get(&url).send().await?;
 
         // 整个这一次请求的意义存疑, 但也许是为了验证 loginName 是否有效
         let url = res.url().as_str().as_bytes();
@@ -29,11 +41,13 @@ impl super::ClassApi {
         let url = cipher.encrypt_ecb(url);
         let url = crypto::bytes2hex(&url);
         let query = [("method", "html5GetPrivateUserInfo"), ("url", &url)];
-        self.client
-            .get("https://iclass.buaa.edu.cn:8346/wc/auth/html5GetPrivateUserInfo")
-            .query(&query)
-            .send()
-            .await?;
+        self.get(format!(
+            "{}/wc/auth/html5GetPrivateUserInfo",
+            self.endpoints.iclass
+        ))
+        .query(&query)
+        .send()
+        .await?;
 
         // 最终登录
         let query = [
@@ -49,13 +63,14 @@ impl super::ClassApi {
         // 包括 opt 模块的一些请求 URL 也是相同的处理
         // 很难想象能有这种错误发生
         let res = self
-            .client
-            .get("https://iclass.buaa.edu.cn:8347/app/user/login.action")
+            .get(format!(
+                "{}/app/user/login.action",
+                self.endpoints.iclass_app
+            ))
             .query(&query)
             .send()
             .await?
-            .bytes()
-            .await?;
+            .bytes();
 
         // 2025.09.07 后端更新, ClassApi 使用了双 token
         // 因为其他 Api 没有这样的需要, 所以我们直接在这里把它们拼起来
@@ -75,31 +90,47 @@ impl super::ClassApi {
     /// Universal Request for ClassApi (Internal)
     ///
     /// **Note**: `token` parameter is already included
-    pub(crate) async fn universal_request<P>(&self, url: &str, payload: &P) -> crate::Result<Bytes>
+    ///
+    /// Set `idempotent` to `false` for operations like checkin, so they are not retried
+    pub(crate) async fn universal_request<P>(
+        &self,
+        url: &str,
+        payload: &P,
+        idempotent: bool,
+    ) -> crate::Result<Bytes>
     where
         P: Serialize + ?Sized,
     {
-        let cred = self.cred.load();
-        if cred.is_expired::<Class>() {
-            self.login().await?;
-        }
-        let token = cred.value::<Class>()?;
+        self.relogin_once::<Class, _, _>(|| async move {
+            if self.cred.load().is_expired::<Class>() {
+                self.login().await?;
+            }
+            let cred = self.cred.load();
+            let token = cred.value::<Class>()?;
 
-        // 因为双 token 机制, 我们暂时只是简单的将其拼在一起
-        let (session, id) = token
-            .split_once('@')
-            .ok_or(Error::auth("Cannot split 'session' and 'id' token").with_label("Class"))?;
+            // 因为双 token 机制, 我们暂时只是简单的将其拼在一起
+            let (session, id) = token
+                .split_once('@')
+                .ok_or(Error::auth("Cannot split 'session' and 'id' token").with_label("Class"))?;
 
-        // 在 URL 中硬编码 id
-        let bytes = self
-            .client
-            .post(format!("{url}?id={id}"))
-            .header("Sessionid", session)
-            .query(&payload)
-            .send()
-            .await?
-            .bytes()
-            .await?;
-        Ok(bytes)
+            // 在 URL 中硬编码 id
+            let bytes = self
+                .post(format!("{url}?id={id}"))
+                .header("Sessionid", session)
+                .query(&payload)
+                .idempotent(idempotent)
+                .send()
+                .await?
+                .bytes();
+
+            // 凭据失效时 STATUS 为 1, 只能从错误信息判断
+            let status = utils::parse_by_tag(&bytes, "\"STATUS\":\"", "\"");
+            let msg = utils::parse_by_tag(&bytes, "\"ERRMSG\":\"", "\"");
+            if status == Some("1") && msg.is_some_and(|m| m.contains("登录")) {
+                return Err(Error::expired("Class"));
+            }
+            Ok(bytes)
+        })
+        .await
     }
 }
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Deserializer, Serialize};
 
-use crate::error::Error;
+use crate::error::{Code, Error};
 use crate::utils;
 use crate::utils::time::DateTime;
 
@@ -15,7 +15,9 @@ impl Res {
         match status {
             Some("0") => Ok(()),
             // 状态码为 2 表示数据为空, 通常是今日没有课程, 或者查询了过时的数据, 没有任何其他字段
-            Some("2") => Err(Error::server("Empty data list").with_label("Class")),
+            Some("2") => Err(Error::server("Empty data list")
+                .with_label("Class")
+                .with_code(Code::ClassEmptyData)),
             Some(s) => {
                 let msg = utils::parse_by_tag(&v, "\"ERRMSG\":\"", "\"");
                 let source = format!("Status Code: {}. Error Message: {:?}", s, msg);
@@ -5,6 +5,7 @@
 mod core;
 mod data;
 mod opt;
+mod test;
 
 pub use data::*;
 
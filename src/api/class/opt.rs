@@ -8,13 +8,24 @@ impl super::ClassApi {
     /// # Query one day's all schedules
     ///
     /// **Input:** DateTime
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "class.query_schedule",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn query_schedule(&self, date: &DateTime) -> crate::Result<Vec<Schedule>> {
-        let url = "https://iclass.buaa.edu.cn:8347/app/course/get_stu_course_sched.action";
+        let url = format!(
+            "{}/app/course/get_stu_course_sched.action",
+            self.endpoints.iclass_app
+        );
         let date = date.date();
         // YYYYMMDD
         let date_str = format!("{}{:02}{:02}", date.year(), date.month() as u8, date.day());
         let payload = [("dateStr", date_str)];
-        let bytes = self.universal_request(url, &payload).await?;
+        let bytes = self.universal_request(&url, &payload, true).await?;
         let res: Vec<Schedule> = Res::parse(&bytes)?;
         Ok(res)
     }
@@ -35,10 +46,21 @@ impl super::ClassApi {
     /// then all of these parallel class courses may appear in your course list.
     /// Although they have different IDs, the queried `CourseSchedule` is the same.
     /// So you better check the status before signing in to avoid the timestamp being overwritten.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "class.query_course",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn query_course(&self, id: &str) -> crate::Result<Vec<Course>> {
-        let url = "https://iclass.buaa.edu.cn:8347/app/choosecourse/get_myall_course.action";
+        let url = format!(
+            "{}/app/choosecourse/get_myall_course.action",
+            self.endpoints.iclass_app
+        );
         let payload = [("user_type", "1"), ("xq_code", id)];
-        let bytes = self.universal_request(url, &payload).await?;
+        let bytes = self.universal_request(&url, &payload, true).await?;
         let res: Vec<Course> = Res::parse(&bytes)?;
         // 需要过滤掉 teacher 为空的字段, 那可能是错误的课程
         let filtered = res
@@ -53,10 +75,21 @@ impl super::ClassApi {
     /// **Input:** Course ID,
     /// from [Course::id] via [super::ClassApi::query_course()]
     /// or [Schedule::course_id] via [super::ClassApi::query_schedule()]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "class.query_course_schedule",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn query_course_schedule(&self, id: &str) -> crate::Result<Vec<CourseSchedule>> {
-        let url = "https://iclass.buaa.edu.cn:8347/app/my/get_my_course_sign_detail.action";
+        let url = format!(
+            "{}/app/my/get_my_course_sign_detail.action",
+            self.endpoints.iclass_app
+        );
         let payload = [("courseId", id)];
-        let bytes = self.universal_request(url, &payload).await?;
+        let bytes = self.universal_request(&url, &payload, true).await?;
         let res: Vec<CourseSchedule> = Res::parse(&bytes)?;
         Ok(res)
     }
@@ -66,13 +99,24 @@ impl super::ClassApi {
     /// **Input:** Schedule ID,
     /// from [Schedule::id] via [super::ClassApi::query_schedule()] (most recommended)
     /// or [CourseSchedule::id] via [super::ClassApi::query_course_schedule()]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "class.checkin",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn checkin(&self, id: &str) -> crate::Result<()> {
         // 2026.03.23. 签到时间现在基于服务器内部时间而非标准 UTC 了.
         // 你在干什么! 怎么敢另立标准的, 其心可诛!
         let timestamp = self.get_time().await?;
-        let url = "http://iclass.buaa.edu.cn:8081/app/course/stu_scan_sign.action";
+        let url = format!(
+            "{}/app/course/stu_scan_sign.action",
+            self.endpoints.iclass_sign
+        );
         let payload = [("courseSchedId", id), ("timestamp", &timestamp)];
-        let bytes = self.universal_request(url, &payload).await?;
+        let bytes = self.universal_request(&url, &payload, false).await?;
         let res: Checkin = Res::parse(&bytes)?;
         if res.status {
             Ok(())
@@ -83,9 +127,12 @@ impl super::ClassApi {
 
     /// Calibrate the internal time of the server
     async fn get_time(&self) -> crate::Result<String> {
-        let url = "http://iclass.buaa.edu.cn:8081/app/common/get_timestamp.action";
+        let url = format!(
+            "{}/app/common/get_timestamp.action",
+            self.endpoints.iclass_sign
+        );
         let payload: [&str; 0] = [];
-        let bytes = self.universal_request(url, &payload).await?;
+        let bytes = self.universal_request(&url, &payload, true).await?;
         Res::check(&bytes)?;
         let timestamp = utils::parse_by_tag(&bytes, "\"timestamp\":", "}")
             .ok_or_else(|| Error::server("Failed to parse timestamp").with_label("Class"))?;
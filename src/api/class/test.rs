@@ -0,0 +1,17 @@
+#[cfg(test)]
+mod tests {
+    use crate::api::class::data::Res;
+    use crate::error::Code;
+
+    #[test]
+    fn test_check() {
+        assert!(Res::check(br#"{"STATUS":"0","result":[]}"#).is_ok());
+
+        let err = Res::check(br#"{"STATUS":"2"}"#).unwrap_err();
+        assert_eq!(Some(&Code::ClassEmptyData), err.code());
+
+        let err = Res::check(r#"{"STATUS":"1","ERRMSG":"参数错误"}"#.as_bytes()).unwrap_err();
+        assert_eq!(None, err.code());
+        assert!(Res::check(b"\r\n").is_err());
+    }
+}
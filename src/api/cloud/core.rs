@@ -1,9 +1,11 @@
 use bytes::Bytes;
-use reqwest::Method;
+use reqwest::{Method, StatusCode};
 use serde::Serialize;
 
+use crate::Endpoints;
 use crate::api::{Cloud, Payload, Sso};
-use crate::error::Error;
+use crate::error::{Code, Error};
+use crate::request::RequestBuilder;
 use crate::store::cookies::Cookie;
 use crate::utils;
 
@@ -28,9 +30,38 @@ use crate::utils;
 // CwIDAQAB
 // -----END PUBLIC KEY-----";
 
+// 按状态码和 cause 区分失败原因
+pub(super) fn status_error(status: StatusCode, cause: Option<&str>) -> Error {
+    // token 失效时返回 401
+    if status == StatusCode::UNAUTHORIZED {
+        return Error::expired("Cloud");
+    }
+    // 重名时返回 409, 部分接口是 403 加上提示
+    if status == StatusCode::CONFLICT
+        || cause.is_some_and(|c| c.contains("同名") || c.contains("已存在"))
+    {
+        return Error::server("Name conflict")
+            .with_label("Cloud")
+            .with_code(Code::CloudNameConflict);
+    }
+    Error::server("Operation failed").with_label("Cloud")
+}
+
 impl super::CloudApi {
     /// # Login to CloudApi
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.login",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn login(&self) -> crate::Result<()> {
+        self.single_flight::<Cloud>(self.login_once()).await
+    }
+
+    async fn login_once(&self) -> crate::Result<()> {
         if self.cred.load().is_expired::<Sso>() {
             self.api::<Sso>().login().await?;
         }
@@ -40,57 +71,53 @@ impl super::CloudApi {
         // 如果 302 到 `callback` 可以使用 refresh_token
         // 问题出在如果刷新 token 过期了会怎样, 会重定向到 `signin` 吗
         // 目前不知道其有效期
-        let url =
-            "https://bhpan.buaa.edu.cn/anyshare/oauth2/login?redirect=%2Fanyshare%2Fzh-cn%2Fportal";
-        let res = self.client.get(url).send().await?;
-        let path = res.url().path();
+        let base = &self.endpoints.bhpan;
+        let host = Endpoints::host(base);
+        let url = format!("{base}/anyshare/oauth2/login?redirect=%2Fanyshare%2Fzh-cn%2Fportal");
+        let signin_url = format!("{base}/oauth2/signin");
+        let callback_url = format!("{base}/anyshare/oauth2/login/callback");
+        let res = self.get(&url).send().await?;
+        let path = res.url().as_str();
 
-        if path == "/oauth2/signin" {
-            // 从 URL 解析 login_challenge=xxx
+        if path.starts_with(&signin_url) {
+            // 从 URL 解析 login_challenge=xxx, 限定在服务的路径下, WebVPN 下各服务共用门户域名
             let login_challenge = res
                 .url()
                 .query()
-                .map(Cookie::parse)
+                .map(|q| Cookie::parse(&format!("{q}; Path={}", res.url().path())))
                 .ok_or(Error::server("No login_challenge").with_label("Cloud"))?;
 
             // 这里有一条需要手动添加的临时 Cookie
             self.cookies.update(|store| {
-                store.insert("bhpan.buaa.edu.cn", login_challenge);
+                store.insert(host, login_challenge);
             });
             // 发起登录请求
-            let url =
-                "https://sso.buaa.edu.cn/login?service=https://bhpan.buaa.edu.cn/oauth2/signin";
-            let res = self.client.get(url).send().await?;
+            let res = self.api::<Sso>().service_login(&signin_url).await;
             // 移除临时 Cookie
             self.cookies.update(|store| {
-                store.remove("bhpan.buaa.edu.cn", "login_challenge");
+                store.remove(host, "login_challenge");
             });
             // 来到回调地址证明登陆成功
             // TODO: 这里有概率失败
-            if res.url().path() != "/anyshare/oauth2/login/callback" {
+            if !res?.final_url.as_str().starts_with(&callback_url) {
                 return Err(Error::server("Login failed. Redirect failed").with_label("Cloud"));
             }
-        } else if path == "/anyshare/oauth2/login/callback" {
-            let url = "https://bhpan.buaa.edu.cn/anyshare/oauth2/login/refreshToken";
-            self.client.get(url).send().await?;
+        } else if path.starts_with(&callback_url) {
+            let url = format!("{base}/anyshare/oauth2/login/refreshToken");
+            self.get(&url).send().await?;
         } else {
             return Err(Error::server("Login failed. Unknown error").with_label("Cloud"));
         }
 
-        match self
-            .cookies
-            .load()
-            .get("bhpan.buaa.edu.cn", "client.oauth2_token")
-            .and_then(|c| c.value())
-        {
+        match self.service_cookie(base, "client.oauth2_token") {
             Some(t) => {
                 self.cred.update(|s| {
-                    s.update::<Cloud>(t.to_string());
+                    s.update::<Cloud>(t);
                 });
                 // 在这里删掉 client.oauth2_(refresh_)token 以外所有 cookie
                 // 防止刷新权限时干扰
                 self.cookies.update(|cookies| {
-                    if let Some(namemap) = cookies.get_mut_map("bhpan.buaa.edu.cn") {
+                    if let Some(namemap) = cookies.get_mut_map(host) {
                         namemap.remove("ory_hydra_consent_csrf_612664744");
                         namemap.remove("ory_hydra_login_csrf_612664744");
                         namemap.remove("SignoutLogin3rdPartyStatus");
@@ -110,26 +137,26 @@ impl super::CloudApi {
     }
 
     pub(crate) async fn token(&self) -> crate::Result<String> {
-        let cred = self.cred.load();
-        if cred.is_expired::<Cloud>() {
+        if self.cred.load().is_expired::<Cloud>() {
             self.login().await?;
         }
+        let cred = self.cred.load();
         Ok(cred.value::<Cloud>()?.to_string())
     }
 
     // 请求实际发起函数
-    pub(crate) async fn request(req: reqwest::RequestBuilder) -> crate::Result<Bytes> {
+    pub(crate) async fn dispatch(req: RequestBuilder<'_, Cloud>) -> crate::Result<Bytes> {
         let res = req.send().await?;
         let status = res.status();
         // 状态码非 200 系异常 JSON 必然在这里产生
         if !status.is_success() {
-            let bytes = res.bytes().await?;
+            let bytes = res.bytes();
+            // 尝试结构化错误
+            // code 字段没有查看 Anyshare 文档的必要性
+            // message 字段基本就是 cause 字段的省略版
+            let cause = utils::parse_by_tag(&bytes, "\"cause\":\"", "\"");
             if log::log_enabled!(log::Level::Error) {
                 log::info!("Status Code: {}", status);
-                // 尝试结构化错误
-                // code 字段没有查看 Anyshare 文档的必要性
-                // message 字段基本就是 cause 字段的省略版
-                let cause = utils::parse_by_tag(&bytes, "\"cause\":\"", "\"");
                 if let Some(cause) = cause {
                     log::info!("Server Cause: {}", cause);
                 } else {
@@ -138,9 +165,9 @@ impl super::CloudApi {
                     log::info!("Raw Response: {}", raw);
                 }
             }
-            return Err(Error::server("Operation failed").with_label("Cloud"));
+            return Err(status_error(status, cause));
         }
-        Ok(res.bytes().await?)
+        Ok(res.bytes())
     }
 
     // 为了让与分享链接相关的操作复用逻辑, 拆分通用请求到上面
@@ -155,21 +182,37 @@ impl super::CloudApi {
     where
         P: Serialize + ?Sized,
     {
-        let token = match token {
-            Some(token) => token,
-            None => &self.token().await?,
-        };
+        // 分享链接的 token 与登录无关, 失效时重新登录也没有用
+        if let Some(token) = token {
+            return self.unireq_once(m, url, payload, token).await;
+        }
+        let m = &m;
+        self.relogin_once::<Cloud, _, _>(|| async move {
+            let token = self.token().await?;
+            let bytes = self.unireq_once(m.clone(), url, payload, &token).await?;
+            self.cred.update(|c| c.refresh::<Cloud>());
+            Ok(bytes)
+        })
+        .await
+    }
 
-        let req = self.client.request(m, url).bearer_auth(token);
+    async fn unireq_once<'a, P>(
+        &self,
+        m: Method,
+        url: &str,
+        payload: &Payload<'a, P>,
+        token: &str,
+    ) -> crate::Result<Bytes>
+    where
+        P: Serialize + ?Sized,
+    {
+        let req = self.request(m, url).bearer_auth(token);
         let req = match payload {
             Payload::Query(f) => req.query(f),
             Payload::Json(j) => req.json(j),
             Payload::Empty => req,
             _ => unreachable!(),
         };
-        let bytes = Self::request(req).await?;
-
-        self.cred.load().refresh::<Cloud>();
-        Ok(bytes)
+        Self::dispatch(req).await
     }
 }
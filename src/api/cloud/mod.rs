@@ -3,6 +3,7 @@
 mod core;
 mod data;
 mod opt;
+mod test;
 
 pub use data::*;
 
@@ -1,6 +1,7 @@
 use reqwest::Method;
 use serde_json::Value;
 
+use crate::Endpoints;
 use crate::api::Payload;
 use crate::error::Error;
 use crate::utils;
@@ -12,11 +13,19 @@ use super::data::{
 
 impl super::CloudApi {
     /// # Get root directory. Better call [RootDir::into_item] to use
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.get_root_dir",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn get_root_dir(&self, root: Root) -> crate::Result<Vec<RootDir>> {
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/entry-doc-lib";
+        let url = format!("{}/api/efast/v1/entry-doc-lib", self.endpoints.bhpan);
         let query = root.as_query();
         let payload = Payload::Query(&query);
-        let bytes = self.unireq(Method::GET, url, &payload, None).await?;
+        let bytes = self.unireq(Method::GET, &url, &payload, None).await?;
         // 纯数组无法放进 Res 结构体
         let res = serde_json::from_slice::<Vec<RootDir>>(&bytes)
             .map_err(|e| parse_error("Can not get root dir", &bytes, e))?;
@@ -24,10 +33,18 @@ impl super::CloudApi {
     }
 
     /// # Get User Root directory
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.get_user_dir",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn get_user_dir(&self) -> crate::Result<Item> {
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/owned-doc-lib";
+        let url = format!("{}/api/efast/v1/owned-doc-lib", self.endpoints.bhpan);
         let payload = Payload::<'_, ()>::Empty;
-        let bytes = self.unireq(Method::GET, url, &payload, None).await?;
+        let bytes = self.unireq(Method::GET, &url, &payload, None).await?;
         let [res]: [RootDir; 1] = serde_json::from_slice(&bytes)
             .map_err(|e| parse_error("No user dir found", &bytes, e))?;
         Ok(res.into_item())
@@ -36,8 +53,16 @@ impl super::CloudApi {
     // 总体来看, `dir` 接口更强大, 几乎支持所有 `file` 操作, 所以尽量统一使用这个
 
     /// # List the contents of a directory
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.list_dir",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn list_dir(&self, item: &Item) -> crate::Result<Dir> {
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/dir/list";
+        let url = format!("{}/api/efast/v1/dir/list", self.endpoints.bhpan);
         let json = serde_json::json!({
             "by": "name", // time/size
             "docid": item.id,
@@ -45,7 +70,7 @@ impl super::CloudApi {
         });
         let payload = Payload::Json(&json);
         let bytes = self
-            .unireq(Method::POST, url, &payload, item.token())
+            .unireq(Method::POST, &url, &payload, item.token())
             .await?;
         let mut res: Dir = Res::parse(&bytes, "Can not get dir list")?;
         // 插入分享链接的授权 token, 后续浏览目录, 另存为, 下载需要这个
@@ -62,15 +87,23 @@ impl super::CloudApi {
     }
 
     /// # Get the size of an item
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.get_item_size",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn get_item_size(&self, item: &Item) -> crate::Result<Size> {
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/dir/size";
+        let url = format!("{}/api/efast/v1/dir/size", self.endpoints.bhpan);
         let json = serde_json::json!({
             "docid": item.id,
             "onlyrecycle": false
         });
         let payload = Payload::Json(&json);
         let bytes = self
-            .unireq(Method::POST, url, &payload, item.token())
+            .unireq(Method::POST, &url, &payload, item.token())
             .await?;
         let res: Size = Res::parse(&bytes, "Can not get item size")?;
         Ok(res)
@@ -86,29 +119,45 @@ impl super::CloudApi {
     ///     - File: "[Name] [(Number)].[Suffix]", e.g. "file (1).zip"
     ///
     /// **Note**: For dir named like "[Name].[Suffix]", the suggested name will be "[Name] [(Number)].[Suffix]"
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.get_suggest_name",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn get_suggest_name(&self, parent: &Item, name: &str) -> crate::Result<String> {
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/file/getsuggestname";
+        let url = format!("{}/api/efast/v1/file/getsuggestname", self.endpoints.bhpan);
         let json = serde_json::json!({
             "docid": parent.id,
             "name": name,
         });
         let payload = Payload::Json(&json);
-        let bytes = self.unireq(Method::POST, url, &payload, None).await?;
+        let bytes = self.unireq(Method::POST, &url, &payload, None).await?;
         let res = utils::parse_by_tag(&bytes, "\"name\":\"", "\"")
             .ok_or_else(|| parse_error("Can not get suggest name", &bytes, "No 'name' field"))?;
         Ok(res.to_string())
     }
 
     /// # Create directory
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.create_dir",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn create_dir(&self, parent: &Item, name: &str) -> crate::Result<String> {
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/dir/create";
+        let url = format!("{}/api/efast/v1/dir/create", self.endpoints.bhpan);
         let json = serde_json::json!({
             "docid": parent.id,
             "name": name,
             "ondup": 1
         });
         let payload = Payload::Json(&json);
-        let bytes = self.unireq(Method::POST, url, &payload, None).await?;
+        let bytes = self.unireq(Method::POST, &url, &payload, None).await?;
         let res = utils::parse_by_tag(&bytes, "\"docid\":\"", "\"")
             .ok_or_else(|| parse_error("Can not create dir", &bytes, "No 'docid' field"))?;
         Ok(res.to_string())
@@ -116,36 +165,60 @@ impl super::CloudApi {
 
     // 重命名不存在的文件会在上层触发 400 错误
     /// # Rename an item
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.rename_item",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn rename_item(&self, item: &Item, name: &str) -> crate::Result<()> {
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/dir/rename";
+        let url = format!("{}/api/efast/v1/dir/rename", self.endpoints.bhpan);
         let json = serde_json::json!({
             "docid": item.id,
             "name": name,
             "ondup": 1
         });
         let payload = Payload::Json(&json);
-        self.unireq(Method::POST, url, &payload, None).await?;
+        self.unireq(Method::POST, &url, &payload, None).await?;
         Ok(())
     }
 
     /// # Move an item
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.move_item",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn move_item(&self, from: &Item, to: &Item) -> crate::Result<String> {
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/dir/move";
+        let url = format!("{}/api/efast/v1/dir/move", self.endpoints.bhpan);
         let json = serde_json::json!({
             "destparent": to.id,
             "docid": from.id,
             "ondup": 1
         });
         let payload = Payload::Json(&json);
-        let bytes = self.unireq(Method::POST, url, &payload, None).await?;
+        let bytes = self.unireq(Method::POST, &url, &payload, None).await?;
         let res = utils::parse_by_tag(&bytes, "\"docid\":\"", "\"")
             .ok_or_else(|| parse_error("Can not move item", &bytes, "No 'docid' field"))?;
         Ok(res.to_string())
     }
 
     /// # Copy an item
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.copy_item",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn copy_item(&self, from: &Item, to: &Item) -> crate::Result<String> {
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/dir/copy";
+        let url = format!("{}/api/efast/v1/dir/copy", self.endpoints.bhpan);
         let json = serde_json::json!({
             "destparent": to.id,
             "docid": from.id,
@@ -153,19 +226,18 @@ impl super::CloudApi {
         });
         let payload = Payload::Json(&json);
         let bytes = match from.token {
-            None => self.unireq(Method::POST, url, &payload, None).await?,
+            None => self.unireq(Method::POST, &url, &payload, None).await?,
             // 对于分享链接, Copy 起到另存为的作用
             // 主授权 Token 仍然用自己的, 但是需要 Link Token 作为二次授权, 命名成 x-as-authorization
             Some(ref t) => {
                 let token = self.token().await?;
                 let req = self
-                    .client
                     .post(url)
-                    .bearer_auth(token)
+                    .bearer_auth(&token)
                     // 额外的 Token
                     .header("x-as-authorization", format!("Bearer {t}"))
                     .json(&json);
-                Self::request(req).await?
+                Self::dispatch(req).await?
             }
         };
         let res = utils::parse_by_tag(&bytes, "\"docid\":\"", "\"")
@@ -176,20 +248,36 @@ impl super::CloudApi {
     // 文件的接口更干净一点
     // 重复删掉文件也不会报错
     /// # Delete an item to recycle bin
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.delete_item",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn delete_item(&self, item: &Item) -> crate::Result<()> {
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/file/delete";
+        let url = format!("{}/api/efast/v1/file/delete", self.endpoints.bhpan);
         let json = serde_json::json!({
             "docid": item.id,
         });
         let payload = Payload::Json(&json);
-        self.unireq(Method::POST, url, &payload, None).await?;
+        self.unireq(Method::POST, &url, &payload, None).await?;
         Ok(())
     }
 
     /// # List recycle bin contents of user's personal directory
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.list_recycle",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn list_recycle(&self) -> crate::Result<Dir> {
         let id = self.get_user_dir().await?.id;
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/recycle/list";
+        let url = format!("{}/api/efast/v1/recycle/list", self.endpoints.bhpan);
         let json = serde_json::json!({
             "by": "time", // name/size
             "docid": id,
@@ -198,7 +286,7 @@ impl super::CloudApi {
             "start": 0
         });
         let payload = Payload::Json(&json);
-        let res = self.unireq(Method::POST, url, &payload, None).await?;
+        let res = self.unireq(Method::POST, &url, &payload, None).await?;
         let res: Dir = Res::parse(&res, "Can not get recycle dir")?;
         Ok(res)
     }
@@ -206,25 +294,41 @@ impl super::CloudApi {
     /// # Delete an item forever in recycle bin
     ///
     /// **Note**: Delete multiple files need call multiple times.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.delete_recycle_item",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn delete_recycle_item(&self, item: &Item) -> crate::Result<()> {
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/recycle/delete";
+        let url = format!("{}/api/efast/v1/recycle/delete", self.endpoints.bhpan);
         let json = serde_json::json!({
             "docid": item.id,
         });
         let payload = Payload::Json(&json);
-        self.unireq(Method::POST, url, &payload, None).await?;
+        self.unireq(Method::POST, &url, &payload, None).await?;
         Ok(())
     }
 
     /// # Restore an item from recycle bin
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.restore_recycle_item",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn restore_recycle_item(&self, item: &Item) -> crate::Result<String> {
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/recycle/restore";
+        let url = format!("{}/api/efast/v1/recycle/restore", self.endpoints.bhpan);
         let json = serde_json::json!({
             "docid": item.id,
             "ondup": 1
         });
         let payload = Payload::Json(&json);
-        let bytes = self.unireq(Method::POST, url, &payload, None).await?;
+        let bytes = self.unireq(Method::POST, &url, &payload, None).await?;
 
         let res = utils::parse_by_tag(&bytes, "\"docid\":\"", "\"").ok_or_else(|| {
             parse_error("Can not restore recycle item", &bytes, "No 'docid' field")
@@ -233,19 +337,39 @@ impl super::CloudApi {
     }
 
     /// # List all share records of the user
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.share_history",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn share_history(&self) -> crate::Result<Vec<Share>> {
-        let url = "https://bhpan.buaa.edu.cn/api/doc-share/v1/docs-shared-with-anyone";
+        let url = format!(
+            "{}/api/doc-share/v1/docs-shared-with-anyone",
+            self.endpoints.bhpan
+        );
         let payload = Payload::<'_, ()>::Empty;
-        let bytes = self.unireq(Method::GET, url, &payload, None).await?;
+        let bytes = self.unireq(Method::GET, &url, &payload, None).await?;
         let res = Share::parse_history(&bytes)?;
         Ok(res)
     }
 
     // 传入不存在的 id 会在上层触发 400 错误
     /// # Get item share record
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.share_record",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn share_record(&self, item: &Item) -> crate::Result<Vec<Share>> {
         let url = format!(
-            "https://bhpan.buaa.edu.cn/api/shared-link/v1/document/folder/{}?type=anonymous",
+            "{}/api/shared-link/v1/document/folder/{}?type=anonymous",
+            self.endpoints.bhpan,
             item.id.replace(':', "%3A").replace('/', "%2F")
         );
         let payload = Payload::<'_, ()>::Empty;
@@ -260,10 +384,21 @@ impl super::CloudApi {
     /// - Output: New [Share] with ID field filled
     ///
     /// **Note**: The share link can be formed as `https://bhpan.buaa.edu.cn/link/{ID}`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.share_item",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn share_item(&self, mut share: Share) -> crate::Result<Share> {
-        let url = "https://bhpan.buaa.edu.cn/api/shared-link/v1/document/anonymous";
+        let url = format!(
+            "{}/api/shared-link/v1/document/anonymous",
+            self.endpoints.bhpan
+        );
         let payload = Payload::Json(&share);
-        let bytes = self.unireq(Method::POST, url, &payload, None).await?;
+        let bytes = self.unireq(Method::POST, &url, &payload, None).await?;
         let res = utils::parse_by_tag(&bytes, "\"id\":\"", "\"")
             .ok_or_else(|| parse_error("Can not create share link", &bytes, "No 'id' field"))?;
         share.id = res.to_string();
@@ -273,10 +408,18 @@ impl super::CloudApi {
     /// # Update share
     ///
     /// - Input: Updated [Share] from `share_record` or `share_item`
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.share_update",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn share_update(&self, share: &Share) -> crate::Result<()> {
         let url = format!(
-            "https://bhpan.buaa.edu.cn/api/shared-link/v1/document/anonymous/{}",
-            share.id
+            "{}/api/shared-link/v1/document/anonymous/{}",
+            self.endpoints.bhpan, share.id
         );
         let payload = Payload::Json(&share);
         self.unireq(Method::PUT, &url, &payload, None).await?;
@@ -286,10 +429,18 @@ impl super::CloudApi {
     /// # Delete share
     ///
     /// - Input: [Share] from `share_record` or `share_item`
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.share_delete",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn share_delete(&self, share: &Share) -> crate::Result<()> {
         let url = format!(
-            "https://bhpan.buaa.edu.cn/api/shared-link/v1/document/anonymous/{}",
-            share.id
+            "{}/api/shared-link/v1/document/anonymous/{}",
+            self.endpoints.bhpan, share.id
         );
         let payload = Payload::<'_, ()>::Empty;
         self.unireq(Method::DELETE, &url, &payload, None).await?;
@@ -305,10 +456,18 @@ impl super::CloudApi {
     ///     - [super::CloudApi::list_dir] for listing share contents if it's a dir
     ///     - [super::CloudApi::copy_item] for saving to personal directory
     ///     - [super::CloudApi::get_download_url] for downloading files
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.share_parse",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn share_parse(&self, id: &str, pwd: Option<&str>) -> crate::Result<Item> {
         // 首先提前看看是否需要密码
-        let url = format!("https://bhpan.buaa.edu.cn/api/shared-link/v1/links/{}", id);
-        let bytes = self.client.get(&url).send().await?.bytes().await?;
+        let url = format!("{}/api/shared-link/v1/links/{}", self.endpoints.bhpan, id);
+        let bytes = self.get(&url).send().await?.bytes();
         // JSON 不保序, 每一次目标键值的位置都可能不同, 用 serde 解析更稳妥
         let data: Value = serde_json::from_slice(&bytes)?;
         let pwd_required = data["password_required"]
@@ -321,7 +480,7 @@ impl super::CloudApi {
             let pwd = pwd.ok_or_else(|| {
                 Error::parameter("Password required for this share").with_label("Cloud")
             })?;
-            let url = "https://bhpan.buaa.edu.cn/link";
+            let url = format!("{}/link", self.endpoints.bhpan);
             let json = serde_json::json!({
                 "id": id,
                 "type": "anonymous",
@@ -329,41 +488,31 @@ impl super::CloudApi {
                 "password": pwd,
                 // 我们不需要 'linkConfig' 这个 cookie, 不需要其他参数
             });
-            self.client.post(url).json(&json).send().await?;
+            self.post(url).json(&json).send().await?;
         } else {
             // 不需要密码的情况, 直接访问链接就能拿到 cookie: 'link_token:ID'
-            let url = format!("https://bhpan.buaa.edu.cn/link/{}", id);
-            self.client.get(&url).send().await?;
+            let url = format!("{}/link/{}", self.endpoints.bhpan, id);
+            self.get(&url).send().await?;
         }
 
         // 从 cookie 中拿到对应分享链接的的临时 token
         let link_cookie_name = format!("link_token:{}", id);
         let link_token = self
-            .cookies
-            .load()
-            .get("bhpan.buaa.edu.cn", &link_cookie_name)
-            .and_then(|c| c.value())
+            .service_cookie(&self.endpoints.bhpan, &link_cookie_name)
             .ok_or_else(|| Error::server("No link cookie").with_label("Cloud"))?;
 
         // 最后可以在这里拿到分享链接对应的 GNS ID
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/entry-item";
-        let bytes = self
-            .client
-            .get(url)
-            .bearer_auth(link_token)
-            .send()
-            .await?
-            .bytes()
-            .await?;
+        let url = format!("{}/api/efast/v1/entry-item", self.endpoints.bhpan);
+        let bytes = self.get(url).bearer_auth(&link_token).send().await?.bytes();
         // 最后防止污染 cookies 状态, 移除这些临时 cookie
         self.cookies.update(|cookies| {
-            if let Some(namemap) = cookies.get_mut_map("bhpan.buaa.edu.cn") {
+            if let Some(namemap) = cookies.get_mut_map(Endpoints::host(&self.endpoints.bhpan)) {
                 namemap.remove(&link_cookie_name);
                 namemap.remove("INGRESSCOOKIE");
             }
         });
 
-        Ok(Item::parse_from_share_link(&bytes, link_token)?)
+        Ok(Item::parse_from_share_link(&bytes, &link_token)?)
     }
 
     // 下载相关的参数错误也会在上层触发 400 错误
@@ -372,14 +521,14 @@ impl super::CloudApi {
     ///
     /// **Note**: If you pass a dir, it will return a bad URL.
     async fn get_single_download_url(&self, item: &Item) -> crate::Result<String> {
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/file/osdownload";
+        let url = format!("{}/api/efast/v1/file/osdownload", self.endpoints.bhpan);
         let json = serde_json::json!({
             "docid": item.id,
             "authtype": "QUERY_STRING",
         });
         let payload = Payload::Json(&json);
         let bytes = self
-            .unireq(Method::POST, url, &payload, item.token())
+            .unireq(Method::POST, &url, &payload, item.token())
             .await?;
         // 下载链接是 authrequest 数组中的第二个元素
         let res = utils::parse_by_tag(&bytes, ",\"", "\"")
@@ -403,17 +552,17 @@ impl super::CloudApi {
             }
         }
 
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/file/batchdownload";
+        let url = format!("{}/api/efast/v1/file/batchdownload", self.endpoints.bhpan);
         let json = serde_json::json!({
             // 对于分享链接, 根目录文件夹的名字可能包含所有人的名字, 形如 User\\Name
             "name": format!("{}.zip", name),
-            "reqhost": "bhpan.buaa.edu.cn",
+            "reqhost": Endpoints::host(&self.endpoints.bhpan),
             "dirs": dirs,
             "files": files
         });
         let payload = Payload::Json(&json);
         let bytes = self
-            .unireq(Method::POST, url, &payload, items[0].token())
+            .unireq(Method::POST, &url, &payload, items[0].token())
             .await?;
         let mut res = utils::parse_by_tag(&bytes, "\"url\":\"", "\"")
             .ok_or_else(|| parse_error("Can not get download url", &bytes, "No 'url' field"))?
@@ -428,6 +577,14 @@ impl super::CloudApi {
     /// One file or One dir, if dir, the URL will be a zip package of the whole dir.
     ///
     /// **Note**: For multiple items, see `get_batch_url`
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.get_download_url",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn get_download_url(&self, item: &Item) -> crate::Result<String> {
         // 下载单个文件只能用这个, 不然得到的链接无法使用
         // 但如果下载单个文件夹不能用这个, 不然得到的链接也无法使用
@@ -442,6 +599,14 @@ impl super::CloudApi {
     /// Multiple items or at least one dir, get a zip package with the given name.
     ///
     /// **Note**: If single file, get a direct download link, and `name` will be ignored.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.get_batch_url",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn get_batch_url(&self, items: &[&Item], name: &str) -> crate::Result<String> {
         if items.is_empty() {
             return Err(Error::parameter("No valid file selected").with_label("Cloud"));
@@ -460,14 +625,22 @@ impl super::CloudApi {
     /// - Input: Need call [UploadArgs::compute_mini]
     ///
     /// **Note**: When upload to share dir, this will always return false, so just upload directly
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.upload_fast_check",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn upload_fast_check(&self, args: &UploadArgs) -> crate::Result<bool> {
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/file/predupload";
+        let url = format!("{}/api/efast/v1/file/predupload", self.endpoints.bhpan);
         let json = serde_json::json!({
             "slice_md5": args.slice_md5,
             "length": args.length
         });
         let payload = Payload::Json(&json);
-        let bytes = self.unireq(Method::POST, url, &payload, None).await?;
+        let bytes = self.unireq(Method::POST, &url, &payload, None).await?;
         let matched = utils::parse_by_tag(&bytes, "\"match\":", "}")
             .ok_or_else(|| parse_error("Can not check hash", &bytes, "No 'match' field"))?;
         Ok(matched == "true")
@@ -478,8 +651,16 @@ impl super::CloudApi {
     /// - Input: Need call [UploadArgs::compute_full]
     ///
     /// **Note**: Not support upload to share dir, call `upload_small` or `upload_big` directly
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.upload_fast",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn upload_fast(&self, args: &UploadArgs) -> crate::Result<()> {
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/file/dupload";
+        let url = format!("{}/api/efast/v1/file/dupload", self.endpoints.bhpan);
         let json = serde_json::json!({
             "client_mtime": DateTime::millis(),
             "crc32": args.crc32,
@@ -491,7 +672,7 @@ impl super::CloudApi {
             "ondup": 1
         });
         let payload = Payload::Json(&json);
-        let bytes = self.unireq(Method::POST, url, &payload, None).await?;
+        let bytes = self.unireq(Method::POST, &url, &payload, None).await?;
         let success = utils::parse_by_tag(&bytes, "\"success\":", "}")
             .ok_or_else(|| parse_error("Can not upload fast", &bytes, "No 'success' field"))?;
         if success != "true" {
@@ -505,8 +686,16 @@ impl super::CloudApi {
     /// - Input: Need call [UploadArgs::compute_mini]
     ///
     /// **Note**: File size should be less than 5 GiB. Recommended for files smaller than 100 MiB.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.upload_small",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn upload_small(&self, args: &UploadArgs, body: Vec<u8>) -> crate::Result<()> {
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/file/osbeginupload";
+        let url = format!("{}/api/efast/v1/file/osbeginupload", self.endpoints.bhpan);
         let json = serde_json::json!({
             "client_mtime": DateTime::millis(),
             "docid": args.dir,
@@ -516,10 +705,10 @@ impl super::CloudApi {
         });
         let payload = Payload::Json(&json);
         let bytes = self
-            .unireq(Method::POST, url, &payload, args.token())
+            .unireq(Method::POST, &url, &payload, args.token())
             .await?;
         let auth: UploadAuth = Res::parse(&bytes, "Can not get upload auth")?;
-        let req = self.client.put(&auth.authrequest[1]);
+        let req = self.put(&auth.authrequest[1]);
         // 0 号是方法, 1 号是 URL, 剩余的是 Header
         let req = auth.authrequest.iter().skip(2).fold(req, |req, header| {
             if let Some((key, value)) = header.split_once(": ") {
@@ -535,7 +724,7 @@ impl super::CloudApi {
                 .with_label("Cloud")
                 .with_source(format!("HTTP status: {}", status.as_u16())));
         }
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/file/osendupload";
+        let url = format!("{}/api/efast/v1/file/osendupload", self.endpoints.bhpan);
         let json = serde_json::json!({
             "docid": auth.docid,
             "rev": auth.rev,
@@ -543,7 +732,7 @@ impl super::CloudApi {
         let payload = Payload::Json(&json);
         // editor 等无用字段
         let _bytes = self
-            .unireq(Method::POST, url, &payload, args.token())
+            .unireq(Method::POST, &url, &payload, args.token())
             .await?;
         Ok(())
     }
@@ -553,12 +742,23 @@ impl super::CloudApi {
     /// - Input: Need call [UploadArgs::compute_mini]
     ///
     /// **Note**: File size should be less than 100 GiB
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "cloud.upload_big",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn upload_big<R>(&self, args: &UploadArgs, mut reader: R) -> crate::Result<()>
     where
         R: std::io::Read + Send + 'static,
     {
         // 开始上传大文件协议, 获取上传 ID 等
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/file/osinitmultiupload";
+        let url = format!(
+            "{}/api/efast/v1/file/osinitmultiupload",
+            self.endpoints.bhpan
+        );
         let json = serde_json::json!({
             "docid": args.dir,
             "length": args.length,
@@ -568,15 +768,15 @@ impl super::CloudApi {
         });
         let payload = Payload::Json(&json);
         let bytes = self
-            .unireq(Method::POST, url, &payload, args.token())
+            .unireq(Method::POST, &url, &payload, args.token())
             .await?;
         let init = args.parse_init(&bytes)?;
 
         // 上传大文件的分块协议, 分块上传文件
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/file/osuploadpart";
+        let url = format!("{}/api/efast/v1/file/osuploadpart", self.endpoints.bhpan);
         let json = Payload::Json(&init);
         // 原始数据不保序, 但上传要求严格保序, 且只有最后一个分块大小可以不为 PART_SIZE
-        let bytes = self.unireq(Method::POST, url, &json, args.token()).await?;
+        let bytes = self.unireq(Method::POST, &url, &json, args.token()).await?;
         // 我们在这里预排序
         let part = args.parse_part(&bytes)?;
 
@@ -593,7 +793,7 @@ impl super::CloudApi {
                 .map_err(|_| Error::io("Read failed"))?;
             remaining -= to_read;
 
-            let req = self.client.put(&auth[1]);
+            let req = self.put(&auth[1]);
             // 0 号是方法, 1 号是 URL, 剩余的是 Header
             let req = auth.iter().skip(2).fold(req, |req, header| {
                 if let Some((key, value)) = header.split_once(": ") {
@@ -616,7 +816,10 @@ impl super::CloudApi {
         }
 
         // 上传大文件的分块完成协议, 提交分块信息
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/file/oscompleteupload";
+        let url = format!(
+            "{}/api/efast/v1/file/oscompleteupload",
+            self.endpoints.bhpan
+        );
         let json = serde_json::json!({
             "docid": init.docid,
             "rev": init.rev,
@@ -625,12 +828,12 @@ impl super::CloudApi {
         });
         let payload = Payload::Json(&json);
         let bytes = self
-            .unireq(Method::POST, url, &payload, args.token())
+            .unireq(Method::POST, &url, &payload, args.token())
             .await?;
         // HTTP 方法 POST, 上传链接, 授权 Token, Content-Type, 日期. 和一个 XML Body
         let (complete, body) = UploadArgs::parse_complete(&bytes)?;
 
-        let req = self.client.post(&complete[1]);
+        let req = self.post(&complete[1]);
         let req = complete.iter().skip(2).fold(req, |req, header| {
             if let Some((key, value)) = header.split_once(": ") {
                 req.header(key, value)
@@ -650,7 +853,7 @@ impl super::CloudApi {
         // Anyshare 我***啊, 都**有大文件分块上传完成协议了
         // 怎么还得单独要这个上传小文件的协议来注册文件, 文档也不写, 排查了半天, 我*了你的*
         // 上传文件完成协议
-        let url = "https://bhpan.buaa.edu.cn/api/efast/v1/file/osendupload";
+        let url = format!("{}/api/efast/v1/file/osendupload", self.endpoints.bhpan);
         let json = serde_json::json!({
             "docid": init.docid,
             "rev": init.rev,
@@ -658,7 +861,7 @@ impl super::CloudApi {
         let payload = Payload::Json(&json);
         // editor 等无用字段
         let _bytes = self
-            .unireq(Method::POST, url, &payload, args.token())
+            .unireq(Method::POST, &url, &payload, args.token())
             .await?;
 
         Ok(())
@@ -0,0 +1,23 @@
+#[cfg(test)]
+mod tests {
+    use reqwest::StatusCode;
+
+    use crate::api::cloud::core::status_error;
+    use crate::error::Code;
+
+    #[test]
+    fn test_status_error() {
+        let err = status_error(StatusCode::UNAUTHORIZED, None);
+        assert_eq!(Some(&Code::AuthExpired), err.code());
+
+        let err = status_error(StatusCode::CONFLICT, None);
+        assert_eq!(Some(&Code::CloudNameConflict), err.code());
+        let err = status_error(StatusCode::FORBIDDEN, Some("存在同名文件"));
+        assert_eq!(Some(&Code::CloudNameConflict), err.code());
+        let err = status_error(StatusCode::FORBIDDEN, Some("目标文件夹已存在"));
+        assert_eq!(Some(&Code::CloudNameConflict), err.code());
+
+        let err = status_error(StatusCode::FORBIDDEN, Some("没有权限"));
+        assert_eq!(None, err.code());
+    }
+}
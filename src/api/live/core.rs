@@ -4,28 +4,41 @@ use serde::Serialize;
 
 use crate::api::{Live, Payload, Sso};
 use crate::error::Error;
+use crate::utils;
 
 impl super::LiveApi {
     /// # Login to LiveApi
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "live.login",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn login(&self) -> crate::Result<()> {
+        self.single_flight::<Live>(self.login_once()).await
+    }
+
+    async fn login_once(&self) -> crate::Result<()> {
         if self.cred.load().is_expired::<Sso>() {
             self.api::<Sso>().login().await?;
         }
-        let url = "https://yjapi.msa.buaa.edu.cn/casapi/index.php?r=auth/login&auType=cmc&tenant_code=21&forward=https%3A%2F%2Fclassroom.msa.buaa.edu.cn";
-        let verify_url = "https://classroom.msa.buaa.edu.cn/";
-        let res = self.client.get(url).send().await?;
+        let url = format!(
+            "{}/casapi/index.php?r=auth/login&auType=cmc&tenant_code=21&forward={}",
+            self.endpoints.yjapi,
+            utils::url_encode(&self.endpoints.classroom)
+        );
+        let verify_url = format!("{}/", self.endpoints.classroom);
+        let res = self.get(&url).send().await?;
         if res.url().as_str() != verify_url {
-            let text = res.text().await?;
+            let text = res.text();
             return Err(Error::server("Login failed")
                 .with_label("Live")
                 .with_source(text));
         }
-        match self
-            .cookies
-            .load()
-            .get("msa.buaa.edu.cn", "_token")
-            .and_then(|c| c.value())
-        {
+        // Cookie 的 Domain 为 msa.buaa.edu.cn, 按 URL 匹配而非直接按域名查找
+        match self.service_cookie(&self.endpoints.yjapi, "_token") {
             Some(t) => {
                 // 这里的 t 来自 PHP 数组, 使用 URL 编码
                 // 原始格式 a:2:{i:0;s:6:"_token";i:1;s:[TOKEN_LENGTH]:"[TOKEN]";}
@@ -45,6 +58,14 @@ impl super::LiveApi {
     }
 
     /// # Universal Request for LiveApi
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "live.universal_request",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn universal_request<P>(
         &self,
         url: &str,
@@ -54,21 +75,32 @@ impl super::LiveApi {
     where
         P: Serialize + ?Sized,
     {
-        let cred = self.cred.load();
-        if cred.is_expired::<Live>() {
-            self.login().await?;
-        }
-        let token = cred.value::<Live>()?;
+        let method = &method;
+        let payload = &payload;
+        self.relogin_once::<Live, _, _>(|| async move {
+            if self.cred.load().is_expired::<Live>() {
+                self.login().await?;
+            }
+            let cred = self.cred.load();
+            let token = cred.value::<Live>()?;
 
-        let req = self.client.request(method, url).bearer_auth(token);
-        let req = match payload {
-            Payload::Query(p) => req.query(p),
-            Payload::Json(p) => req.json(p),
-            Payload::Empty => req,
-            _ => unreachable!(),
-        };
+            let req = self.request(method.clone(), url).bearer_auth(token);
+            let req = match payload {
+                Payload::Query(p) => req.query(p),
+                Payload::Json(p) => req.json(p),
+                Payload::Empty => req,
+                _ => unreachable!(),
+            };
 
-        let bytes = req.send().await?.bytes().await?;
-        Ok(bytes)
+            let res = req.send().await?;
+            // 凭据失效时 HTTP 状态码仍是 200, 内层 code 为 401
+            if self.is_rejected(&res)
+                || utils::parse_by_tag(&res.body, "\"code\":", ",") == Some("401")
+            {
+                return Err(Error::expired("Live"));
+            }
+            Ok(res.bytes())
+        })
+        .await
     }
 }
@@ -3,8 +3,8 @@ use reqwest::Method;
 use serde::Serialize;
 
 use crate::api::{Payload, Spoc, Sso};
-use crate::crypto;
 use crate::error::Error;
+use crate::{crypto, utils};
 
 // 逆向出来的密钥和初始向量, 用于 AES 加密请求体,
 // 不过既然写死了为什么不用 ECB 而用 CBC 模式啊
@@ -15,17 +15,29 @@ const SPOC_AES_IV: &[u8] = b"ocni12345678inco";
 
 impl super::SpocApi {
     /// # Login to SpocApi
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "spoc.login",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn login(&self) -> crate::Result<()> {
+        self.single_flight::<Spoc>(self.login_once()).await
+    }
+
+    async fn login_once(&self) -> crate::Result<()> {
         if self.cred.load().is_expired::<Sso>() {
             self.api::<Sso>().login().await?;
         }
 
         let res = self
-            .client
-            .get("https://spoc.buaa.edu.cn/spocnewht/cas")
+            .get(format!("{}/spocnewht/cas", self.endpoints.spoc))
             .send()
             .await?;
-        if res.url().as_str().contains("https://sso.buaa.edu.cn/login") {
+        let sso_url = format!("{}/login", self.endpoints.sso);
+        if res.url().as_str().contains(&sso_url) {
             return Err(Error::server("Redirect failed").with_label("Spoc"));
         }
         let mut query = res.url().query_pairs();
@@ -47,6 +59,14 @@ impl super::SpocApi {
     /// **Note**: You should use other existing APIs first.
     ///
     /// If the API you need but is not implemented, you can extend it with this universal request API.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "spoc.universal_request",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn universal_request<P>(
         &self,
         url: &str,
@@ -56,30 +76,41 @@ impl super::SpocApi {
     where
         P: Serialize + ?Sized,
     {
-        let cred = self.cred.load();
-        if cred.is_expired::<Spoc>() {
-            self.login().await?;
-        }
-        let token = cred.value::<Spoc>()?;
-
-        let req = self.client.request(method, url).header("Token", token);
-        let req = match payload {
-            Payload::Query(q) => req.query(q),
-            // 它们是不是把这个玩意忘了, 做了这么多加密结果只有一个接口在用
-            Payload::Json(j) => {
-                // 构造请求体, 使用 AES 加密请求参数, Base64 编码
-                let aes = crypto::aes::Aes128::new(SPOC_AES_KEY);
-                // TODO: 考虑直接序列化到加密器中
-                let data = serde_json::to_vec(j)?;
-                let data = aes.encrypt_cbc(&data, SPOC_AES_IV);
-                let data = crypto::encode_base64(data);
-                let json = serde_json::json!({ "param": data });
-                req.json(&json)
+        let method = &method;
+        let payload = &payload;
+        self.relogin_once::<Spoc, _, _>(|| async move {
+            if self.cred.load().is_expired::<Spoc>() {
+                self.login().await?;
             }
-            _ => unreachable!(),
-        };
+            let cred = self.cred.load();
+            let token = cred.value::<Spoc>()?;
+
+            let req = self.request(method.clone(), url).header("Token", token);
+            let req = match payload {
+                Payload::Query(q) => req.query(q),
+                // 它们是不是把这个玩意忘了, 做了这么多加密结果只有一个接口在用
+                Payload::Json(j) => {
+                    // 构造请求体, 使用 AES 加密请求参数, Base64 编码
+                    let aes = crypto::aes::Aes128::new(SPOC_AES_KEY);
+                    // TODO: 考虑直接序列化到加密器中
+                    let data = serde_json::to_vec(j)?;
+                    let data = aes.encrypt_cbc(&data, SPOC_AES_IV);
+                    let data = crypto::encode_base64(data);
+                    let json = serde_json::json!({ "param": data });
+                    req.json(&json)
+                }
+                _ => unreachable!(),
+            };
 
-        let res = req.send().await?.bytes().await?;
-        Ok(res)
+            let res = req.send().await?;
+            // 凭据失效时 HTTP 状态码仍是 200, 响应体 code 为 401
+            if self.is_rejected(&res)
+                || utils::parse_by_tag(&res.body, "\"code\":", ",") == Some("401")
+            {
+                return Err(Error::expired("Spoc"));
+            }
+            Ok(res.bytes())
+        })
+        .await
     }
 }
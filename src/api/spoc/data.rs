@@ -438,13 +438,20 @@ pub struct UploadRes {
     pub size: String,
     /// File MD5
     pub md5: String,
+    // 上传时所用的 spoc 端点, 反序列化得到的值为空
+    #[serde(skip)]
+    pub(super) base: String,
 }
 
 impl UploadRes {
-    /// Convert to download URL
+    /// Convert to download URL, on the same host the file was uploaded to
     pub fn as_url(&self) -> String {
+        let base = match self.base.as_str() {
+            "" => "https://spoc.buaa.edu.cn",
+            base => base,
+        };
         format!(
-            "https://spoc.buaa.edu.cn/inco-filesystem/fileManagerSystem/downLoadFile?scjlid={}",
+            "{base}/inco-filesystem/fileManagerSystem/downLoadFile?scjlid={}",
             self.id
         )
     }
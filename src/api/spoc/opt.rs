@@ -11,28 +11,44 @@ use super::{
 
 impl super::SpocApi {
     /// Get current week
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "spoc.get_week",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn get_week(&self) -> crate::Result<Week> {
-        let url = "https://spoc.buaa.edu.cn/spocnewht/inco/ht/queryOne";
+        let url = format!("{}/spocnewht/inco/ht/queryOne", self.endpoints.spoc);
         // SQL ID 是固定值, 应该是对应的数据库键什么的
         let json = serde_json::json!({
             "sqlid": "17275975753144ed8d6fe15425677f752c936d97de1bab76"
         });
         let payload = Payload::Json(&json);
-        let bytes = self.universal_request(url, Method::POST, payload).await?;
+        let bytes = self.universal_request(&url, Method::POST, payload).await?;
         let res: Week = Res::parse(&bytes)?;
         Ok(res)
     }
 
     /// Query schedule of a week
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "spoc.query_week_schedules",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn query_week_schedules(&self, week: &Week) -> crate::Result<Vec<Schedule>> {
-        let url = "https://spoc.buaa.edu.cn/spocnewht/jxkj/queryRlData";
+        let url = format!("{}/spocnewht/jxkj/queryRlData", self.endpoints.spoc);
         let query = [
             ("rllx", "1"), // 日历类型
             ("zksrq", &week.date.0),
             ("zjsrq", &week.date.1),
         ];
         let payload = Payload::Query(&query);
-        let bytes = self.universal_request(url, Method::GET, payload).await?;
+        let bytes = self.universal_request(&url, Method::GET, payload).await?;
         let res: Vec<Schedule> = Res::parse(&bytes)?;
         Ok(res)
     }
@@ -41,39 +57,71 @@ impl super::SpocApi {
     ///
     /// `term` format: "yyyy-yyyyt", e.g. "2025-20261" for 2025 fall semester.
     /// Can get from [Week::term]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "spoc.query_courses",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn query_courses(&self, term: &str) -> crate::Result<Vec<Course>> {
-        let url = "https://spoc.buaa.edu.cn/spocnewht/jxkj/queryKclb";
+        let url = format!("{}/spocnewht/jxkj/queryKclb", self.endpoints.spoc);
         let query = [("xnxq", term)];
         let payload = Payload::Query(&query);
-        let bytes = self.universal_request(url, Method::GET, payload).await?;
+        let bytes = self.universal_request(&url, Method::GET, payload).await?;
         let res: Vec<Course> = Res::parse(&bytes)?;
         Ok(res)
     }
 
     /// Query homeworks
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "spoc.query_homeworks",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn query_homeworks(&self, course: &Course) -> crate::Result<Vec<Homework>> {
-        let url = "https://spoc.buaa.edu.cn/spocnewht/kczy/queryXsZyList";
+        let url = format!("{}/spocnewht/kczy/queryXsZyList", self.endpoints.spoc);
         // 有缓存的情况下没有前两个参数也正常, 但没缓存就会返回 Null
         let query = [("flag", "1"), ("sflx", "2"), ("sskcid", &course.id)];
         let payload = Payload::Query(&query);
-        let bytes = self.universal_request(url, Method::GET, payload).await?;
+        let bytes = self.universal_request(&url, Method::GET, payload).await?;
         let res: Data<Vec<Homework>> = Res::parse(&bytes)?;
         Ok(res.0)
     }
 
     /// Query homework detail
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "spoc.query_homework_detail",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn query_homework_detail(&self, hw: &Homework) -> crate::Result<HomeworkDetail> {
-        let url = "https://spoc.buaa.edu.cn/spocnewht/kczy/queryKczyInfoByid";
+        let url = format!("{}/spocnewht/kczy/queryKczyInfoByid", self.endpoints.spoc);
         let query = [("id", &hw.id)];
         let payload = Payload::Query(&query);
-        let bytes = self.universal_request(url, Method::GET, payload).await?;
+        let bytes = self.universal_request(&url, Method::GET, payload).await?;
         let res: HomeworkDetail = Res::parse(&bytes)?;
         Ok(res)
     }
 
     /// Submit homework
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "spoc.submit_homework",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn submit_homework(&self, hw: &Homework, file: &UploadRes) -> crate::Result<()> {
-        let url = "https://spoc.buaa.edu.cn/spocnewht/kczy/submitKcz2";
+        let url = format!("{}/spocnewht/kczy/submitKcz2", self.endpoints.spoc);
         // TODO: name 字段真的重要吗, 服务器已经将 ID 与 name 关联在一起了
         let form = [
             ("ytjcs", "2"),
@@ -86,7 +134,7 @@ impl super::SpocApi {
         ];
         // 原则上是 form, 不过既然能用就不增加复杂度了
         let payload = Payload::Query(&form);
-        let bytes = self.universal_request(url, Method::POST, payload).await?;
+        let bytes = self.universal_request(&url, Method::POST, payload).await?;
         // 能写出这种返回值的家里请高人了, msg_en 是给你这么用的吗
         // {"code":200,"msg":"操作成功","msg_en":"操作时间xxx","content":null}
         let _res: Option<()> = Res::parse(&bytes)?;
@@ -120,8 +168,16 @@ impl super::SpocApi {
     ///
     /// **Note**: For some special types of files (like DLL, PDB, EXE), the server may reject the upload.
     /// You can try renaming the file with a common extension (like .pdf) or using a compressed archive.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "spoc.upload_callback",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn upload_callback<R, F>(
-        client: &reqwest::Client,
+        &self,
         args: &UploadArgs,
         reader: R,
         progress: F,
@@ -130,15 +186,16 @@ impl super::SpocApi {
         R: std::io::Read,
         F: Fn(UploadProgress),
     {
-        let upload_url = "https://spoc.buaa.edu.cn/inco-filesystem/fileManagerSystem/uploadFile";
-        let merge_url = "https://spoc.buaa.edu.cn/inco-filesystem/fileManagerSystem/mergeFile";
+        let base = &self.endpoints.spoc;
+        let upload_url = format!("{base}/inco-filesystem/fileManagerSystem/uploadFile");
+        let merge_url = format!("{base}/inco-filesystem/fileManagerSystem/mergeFile");
 
         // 检查上传状态, 是否已经存在或者断点续传
-        let res = client.get(upload_url).query(args).send().await?;
-        let bytes = res.bytes().await?;
+        let res = self.get(&upload_url).query(args).send().await?;
+        let bytes = res.bytes();
         let status = UploadStatus::from_json(&bytes)?;
-        match status {
-            UploadStatus::Complete(c) => Ok(c),
+        let mut res: UploadRes = match status {
+            UploadStatus::Complete(c) => c,
             UploadStatus::Partial(partial) => {
                 let mut done = partial.len() as u64;
                 let total = args.total_chunks();
@@ -198,8 +255,8 @@ impl super::SpocApi {
 
                     // 一些无用的 {"data":null,"mc":false}
                     // TODO: 但是为空时也是出错
-                    let _res = client
-                        .post(upload_url)
+                    let _res = self
+                        .post(&upload_url)
                         .query(&query)
                         .header("Content-Type", content_type)
                         .body(body)
@@ -213,8 +270,8 @@ impl super::SpocApi {
 
                 // 合并上传的块
                 let query = args.to_merge();
-                let res = client.post(merge_url).query(&query).send().await?;
-                let bytes = res.bytes().await?;
+                let res = self.post(&merge_url).query(&query).send().await?;
+                let bytes = res.bytes();
                 // 特殊类型, 如 dll, pdb, exe 等不支持直接 merge, 需要打包成 zip 等上传
                 if bytes.is_empty() {
                     return Err(
@@ -222,11 +279,12 @@ impl super::SpocApi {
                             .with_label("Spoc"),
                     );
                 }
-                let res = serde_json::from_slice(&bytes)?;
-
-                Ok(res)
+                serde_json::from_slice(&bytes)?
             }
-        }
+        };
+        // 下载地址跟随当前的 spoc 端点
+        res.base = base.clone();
+        Ok(res)
     }
 
     /// # Upload file
@@ -235,10 +293,18 @@ impl super::SpocApi {
     ///
     /// **Note**: For some special types of files (like DLL, PDB, EXE), the server may reject the upload.
     /// You can try renaming the file with a common extension (like .pdf) or using a compressed archive.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "spoc.upload",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn upload<R>(&self, args: &UploadArgs, reader: R) -> crate::Result<UploadRes>
     where
         R: std::io::Read,
     {
-        Self::upload_callback(&self.client, args, reader, |_| {}).await
+        self.upload_callback(args, reader, |_| {}).await
     }
 }
@@ -3,31 +3,32 @@ use serde::Serialize;
 
 use crate::api::{Payload, Srs, Sso};
 use crate::error::Error;
+use crate::utils;
 
 impl super::SrsApi {
     /// Login to SrsApi
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "srs.login",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn login(&self) -> crate::Result<()> {
-        if self.cred.load().is_expired::<Sso>() {
-            self.api::<Sso>().login().await?;
-        }
+        self.single_flight::<Srs>(self.login_once()).await
+    }
 
-        let url = "https://sso.buaa.edu.cn/login?service=https%3A%2F%2Fbyxk.buaa.edu.cn%2Fxsxk%2Fauth%2Fcas";
+    async fn login_once(&self) -> crate::Result<()> {
+        let service = format!("{}/xsxk/auth/cas", self.endpoints.byxk);
         // 获取 JSESSIONID
-        let res = self.client.get(url).send().await?;
-        // 未转跳就证明登录过期
-        if res.url().as_str() == url {
-            return Err(Error::server("Redirect failed").with_label("Srs"));
-        }
+        self.api::<Sso>().service_login(&service).await?;
         // 储存 token
         let token = self
-            .cookies
-            .load()
-            .get_map("byxk.buaa.edu.cn")
-            .and_then(|c| c.get("token"))
-            .and_then(|c| c.value())
+            .service_cookie(&self.endpoints.byxk, "token")
             .ok_or(Error::server("Login failed. No Token").with_label("Srs"))?;
         self.cred.update(|s| {
-            s.update::<Srs>(token.to_string());
+            s.update::<Srs>(token);
         });
 
         Ok(())
@@ -37,27 +38,42 @@ impl super::SrsApi {
         &self,
         url: &str,
         payload: Payload<'a, P>,
+        idempotent: bool,
     ) -> crate::Result<Bytes>
     where
         P: Serialize + ?Sized,
     {
-        let cred = self.cred.load();
-        if cred.is_expired::<Srs>() {
-            self.login().await?;
-        }
-        let token = cred.value::<Srs>()?;
-
-        let req = self.client.post(url).header("Authorization", token);
-
-        let req = match payload {
-            Payload::Token => req.query(&[("token", token)]),
-            Payload::Form(f) => req.form(f),
-            Payload::Json(j) => req.json(j),
-            Payload::Empty => req,
-            _ => unreachable!(),
-        };
-
-        let bytes = req.send().await?.bytes().await?;
-        Ok(bytes)
+        let payload = &payload;
+        self.relogin_once::<Srs, _, _>(|| async move {
+            if self.cred.load().is_expired::<Srs>() {
+                self.login().await?;
+            }
+            let cred = self.cred.load();
+            let token = cred.value::<Srs>()?;
+
+            // 查询接口也是 POST, 由调用方决定能否重试, 选课退课不能重放
+            let req = self
+                .post(url)
+                .header("Authorization", token)
+                .idempotent(idempotent);
+
+            let req = match payload {
+                Payload::Token => req.query(&[("token", token)]),
+                Payload::Form(f) => req.form(f),
+                Payload::Json(j) => req.json(j),
+                Payload::Empty => req,
+                _ => unreachable!(),
+            };
+
+            let res = req.send().await?;
+            // 凭据失效时 HTTP 状态码仍是 200, 响应体 code 为 401
+            if self.is_rejected(&res)
+                || utils::parse_by_tag(&res.body, "\"code\":", ",") == Some("401")
+            {
+                return Err(Error::expired("Srs"));
+            }
+            Ok(res.bytes())
+        })
+        .await
     }
 }
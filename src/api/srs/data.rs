@@ -2,7 +2,7 @@ use serde::de::Deserializer;
 use serde::{Deserialize, Serialize, Serializer};
 
 use crate::api::Data;
-use crate::error::Error;
+use crate::error::{Code, Error};
 use crate::utils::time::DateTime;
 
 #[derive(Deserialize)]
@@ -16,12 +16,30 @@ impl<'de, T: Deserialize<'de>> Res<T> {
     pub(crate) fn parse(v: &'de [u8]) -> crate::Result<T> {
         let res: Res<T> = serde_json::from_slice(&v)?;
         if res.code != 200 {
-            return Err(Error::server(format!("Response: {}", res.msg)).with_label("Srs"));
+            let err = Error::server(format!("Response: {}", res.msg)).with_label("Srs");
+            return Err(match error_code(&res.msg) {
+                Some(code) => err.with_code(code),
+                None => err,
+            });
         }
         Ok(res.data)
     }
 }
 
+// 失败时 code 不区分原因, 只能从提示信息判断
+fn error_code(msg: &str) -> Option<Code> {
+    if msg.contains("冲突") {
+        Some(Code::SrsConflict)
+    } else if ["不在选课时间", "未开放", "已结束", "已关闭"]
+        .iter()
+        .any(|s| msg.contains(s))
+    {
+        Some(Code::SrsBatchClosed)
+    } else {
+        None
+    }
+}
+
 // ====================
 // 反/序列化布尔值
 // ====================
@@ -6,10 +6,18 @@ use super::{Config, Course, Filter, Opt, Res, Selected};
 
 impl super::SrsApi {
     /// # Get configuration of SrsApi
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "srs.get_config",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn get_config(&self) -> crate::Result<Config> {
-        let url = "https://byxk.buaa.edu.cn/xsxk/web/studentInfo";
+        let url = format!("{}/xsxk/web/studentInfo", self.endpoints.byxk);
         let payload = Payload::<'_, ()>::Token;
-        let bytes = self.universal_request(url, payload).await?;
+        let bytes = self.universal_request(&url, payload, true).await?;
         let res: Data<Config> = Res::parse(&bytes)?;
         Ok(res.0)
     }
@@ -20,9 +28,17 @@ impl super::SrsApi {
     /// **Note**: Do not need login
     ///
     /// **Note**: Only for [super::SrsApi::pre_select_course]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "srs.get_batch",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn get_batch(&self) -> crate::Result<String> {
-        let url = "https://byxk.buaa.edu.cn/xsxk/profile/index.html";
-        let bytes = self.client.get(url).send().await?.bytes().await?;
+        let url = format!("{}/xsxk/profile/index.html", self.endpoints.byxk);
+        let bytes = self.get(url).send().await?.bytes();
         let id = utils::parse_by_tag(&bytes, "\"code\":\"", "\"");
         match id {
             Some(i) => Ok(i.to_string()),
@@ -31,10 +47,18 @@ impl super::SrsApi {
     }
 
     /// # Query Course
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "srs.query_course",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn query_course(&self, filter: &Filter) -> crate::Result<Vec<Course>> {
-        let url = "https://byxk.buaa.edu.cn/xsxk/elective/buaa/clazz/list";
+        let url = format!("{}/xsxk/elective/buaa/clazz/list", self.endpoints.byxk);
         let payload = Payload::Json(filter);
-        let bytes = self.universal_request(url, payload).await?;
+        let bytes = self.universal_request(&url, payload, true).await?;
         let mut res: Data<Vec<Course>> = Res::parse(&bytes)?;
         // 手动插入 scope, 方便后续调用选课相关 API
         res.0.iter_mut().for_each(|c| c.scope = filter.scope);
@@ -46,10 +70,18 @@ impl super::SrsApi {
     /// **Note**: Only for pre-selection. Late-selection use `query_selected`
     ///
     /// **Note**: Collect into `Vec` according to volunteer grouping
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "srs.query_pre_selected",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn query_pre_selected(&self) -> crate::Result<Vec<Vec<Selected>>> {
-        let url = "https://byxk.buaa.edu.cn/xsxk/volunteer/select";
+        let url = format!("{}/xsxk/volunteer/select", self.endpoints.byxk);
         let payload = Payload::<'_, ()>::Empty;
-        let bytes = self.universal_request(url, payload).await?;
+        let bytes = self.universal_request(&url, payload, true).await?;
         let res: Data<Vec<Vec<Selected>>> = Res::parse(&bytes)?;
         Ok(res.0)
     }
@@ -57,10 +89,18 @@ impl super::SrsApi {
     /// # Query Selected Course
     ///
     /// **Note**: Only for late-selection. Pre-selection use `query_pre_selected`
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "srs.query_selected",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn query_selected(&self) -> crate::Result<Vec<Selected>> {
-        let url = "https://byxk.buaa.edu.cn/xsxk/elective/select";
+        let url = format!("{}/xsxk/elective/select", self.endpoints.byxk);
         let payload = Payload::<'_, ()>::Empty;
-        let bytes = self.universal_request(url, payload).await?;
+        let bytes = self.universal_request(&url, payload, true).await?;
         let res: Vec<Selected> = Res::parse(&bytes)?;
         Ok(res)
     }
@@ -77,10 +117,18 @@ impl super::SrsApi {
     /// **Note**: You cannot call login before calling this, otherwise the verification will fail
     ///
     /// - Input: `opt`: call `as_opt` on [Course]. And must call `set_batch` and `set_index` on [Opt]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "srs.pre_select_course",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn pre_select_course<'a>(&self, opt: &'a Opt<'a>) -> crate::Result<()> {
-        let url = "https://byxk.buaa.edu.cn/xsxk/elective/buaa/clazz/add";
+        let url = format!("{}/xsxk/elective/buaa/clazz/add", self.endpoints.byxk);
         let payload = Payload::Form(&opt);
-        let bytes = self.universal_request(url, payload).await?;
+        let bytes = self.universal_request(&url, payload, false).await?;
         let _: Option<()> = Res::parse(&bytes)?;
 
         Ok(())
@@ -93,10 +141,18 @@ impl super::SrsApi {
     /// **Note**: You cannot call login before calling this, otherwise the verification will fail
     ///
     /// - Input: `opt`: call `as_opt` on [Course]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "srs.select_course",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn select_course<'a>(&self, opt: &'a Opt<'a>) -> crate::Result<()> {
-        let url = "https://byxk.buaa.edu.cn/xsxk/elective/buaa/clazz/add";
+        let url = format!("{}/xsxk/elective/buaa/clazz/add", self.endpoints.byxk);
         let payload = Payload::Form(opt);
-        let bytes = self.universal_request(url, payload).await?;
+        let bytes = self.universal_request(&url, payload, false).await?;
         let _: Option<()> = Res::parse(&bytes)?;
 
         Ok(())
@@ -107,10 +163,18 @@ impl super::SrsApi {
     /// **Note**: You cannot call login before calling this, otherwise the verification will fail
     ///
     /// - Input: `opt`: call `as_opt` on [Course] or [Selected]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "srs.drop_course",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn drop_course<'a>(&self, opt: &'a Opt<'a>) -> crate::Result<()> {
-        let url = "https://byxk.buaa.edu.cn/xsxk/elective/clazz/del";
+        let url = format!("{}/xsxk/elective/clazz/del", self.endpoints.byxk);
         let payload = Payload::Form(opt);
-        let bytes = self.universal_request(url, payload).await?;
+        let bytes = self.universal_request(&url, payload, false).await?;
         let _: Option<()> = Res::parse(&bytes)?;
 
         Ok(())
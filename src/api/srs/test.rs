@@ -0,0 +1,31 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::api::srs::data::Res;
+    use crate::error::Code;
+
+    fn code(msg: &str) -> Option<Code> {
+        let raw = format!(r#"{{"code":500,"msg":"{msg}","data":null}}"#);
+        Res::<Value>::parse(raw.as_bytes())
+            .unwrap_err()
+            .code()
+            .copied()
+    }
+
+    #[test]
+    fn test_error_code() {
+        assert_eq!(
+            Some(Code::SrsConflict),
+            code("该课程与已选课程上课时间冲突")
+        );
+        assert_eq!(Some(Code::SrsBatchClosed), code("当前不在选课时间范围内"));
+        assert_eq!(Some(Code::SrsBatchClosed), code("选课批次未开放"));
+        assert_eq!(Some(Code::SrsBatchClosed), code("选课已结束"));
+        assert_eq!(Some(Code::SrsBatchClosed), code("选课批次已关闭"));
+        assert_eq!(None, code("课容量已满"));
+
+        let raw = r#"{"code":200,"msg":"操作成功","data":1}"#;
+        assert_eq!(Value::from(1), Res::<Value>::parse(raw.as_bytes()).unwrap());
+    }
+}
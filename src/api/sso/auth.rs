@@ -1,79 +1,278 @@
 use log::trace;
 
 use crate::api::Sso;
-use crate::error::Error;
+use crate::error::{Code, Error};
+use crate::request::Url;
 use crate::utils;
 
+use super::challenge::{self, LoginChallenge, PageChallenge};
+use super::{LoginReport, LoginWarning};
+
+// 验证码答错后最多重新作答的次数
+const MAX_CHALLENGES: usize = 3;
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+// 失败页提示用户名或密码错误时, 再次提交只会累计错误次数
+fn wrong_password(page: &[u8]) -> bool {
+    String::from_utf8_lossy(page).contains("密码错误")
+}
+
+// 从失败页的提示文案识别锁定和频率限制, 其他情况无法与密码错误区分
+fn failure_code(page: &[u8]) -> Option<Code> {
+    let text = String::from_utf8_lossy(page);
+    if text.contains("锁定") {
+        Some(Code::AuthLocked)
+    } else if text.contains("次数过多") || text.contains("过于频繁") {
+        Some(Code::AuthTooManyAttempts)
+    } else {
+        None
+    }
+}
+
 impl super::SsoApi {
     /// # Login to SSO
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "sso.login",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn login(&self) -> crate::Result<()> {
-        // TODO: VPN 方法使用下面的 URL, 但我还没想好怎么分组
-        // "https://d.buaa.edu.cn/https/77726476706e69737468656265737421e3e44ed225256951300d8db9d6562d/login?service=https%3A%2F%2Fd.buaa.edu.cn%2Flogin%3Fcas_login%3Dtrue";
-        // "https://d.buaa.edu.cn/";
-        let login_url = "https://sso.buaa.edu.cn/login";
-        let verify_url = "https://uc.buaa.edu.cn/";
+        self.login_report().await.map(drop)
+    }
+
+    /// # Login to SSO with Report
+    ///
+    /// Same as [`super::SsoApi::login`], but tells whether a warning page was ignored,
+    /// e.g. the password is weak and should be changed
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "sso.login_report",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
+    pub async fn login_report(&self) -> crate::Result<LoginReport> {
+        // 等到了别人的登录结果时, 这里不会被赋值
+        let mut report = LoginReport::Shared;
+        self.single_flight::<Sso>(async {
+            report = self.login_once().await?;
+            Ok(())
+        })
+        .await?;
+        Ok(report)
+    }
+
+    /// # Logout from SSO
+    ///
+    /// Revokes the TGC on the server and resets the local SSO session.
+    /// Sessions already established with other groups stay valid, reset them separately if needed
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "sso.logout",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
+    pub async fn logout(&self) -> crate::Result<()> {
+        let url = format!("{}/logout", self.endpoints.sso);
+        self.get(&url).send().await?;
+        self.reset();
+        Ok(())
+    }
+
+    // 只访问登录页, 被重定向走说明 TGC 仍然有效, 不会提交密码
+    pub(crate) async fn probe(&self) -> crate::Result<bool> {
+        let (login_url, verify_url) = self.login_urls();
+        let res = self.get(&login_url).send().await?;
+        Ok(res.url().as_str() == verify_url)
+    }
+
+    // WebVPN 模式下以门户为 service 登录, 成功后门户下发自己的票据
+    fn login_urls(&self) -> (String, String) {
+        match &self.endpoints.webvpn {
+            Some(portal) => {
+                let service = format!("{portal}/login?cas_login=true");
+                let login_url = format!(
+                    "{}/login?service={}",
+                    self.endpoints.sso,
+                    utils::url_encode(&service)
+                );
+                (login_url, format!("{portal}/"))
+            }
+            None => (
+                format!("{}/login", self.endpoints.sso),
+                format!("{}/", self.endpoints.uc),
+            ),
+        }
+    }
+
+    async fn login_once(&self) -> crate::Result<LoginReport> {
+        let (login_url, verify_url) = self.login_urls();
         let cred = self.cred.load();
         let un = cred.username()?;
         let pw = cred.password()?;
         // 获取登录页 execution 值
-        let res = self.client.get(login_url).send().await?;
+        let res = self.get(&login_url).send().await?;
         // 重定向到这里说明 Cookie 有效, 但无法刷新
         if res.url().as_str() == verify_url {
             trace!("SSO still valid");
-            return Ok(());
+            return Ok(LoginReport::Valid);
         }
-        let bytes = res.bytes().await?;
-        let execution = match utils::parse_by_tag(&bytes, "\"execution\" value=\"", "\"") {
-            Some(s) => s,
-            None => {
-                return Err(Error::server("Login failed. No Execution Value").with_label("Sso"));
+        let mut page_url = res.url().clone();
+        let mut page = res.bytes();
+        // 验证码答错时会换一张再出, 直到用完次数
+        for _ in 0..MAX_CHALLENGES {
+            let execution = match utils::parse_by_tag(&page, "\"execution\" value=\"", "\"") {
+                Some(s) => s.to_string(),
+                None => {
+                    return Err(Error::server("Login failed. No Execution Value").with_label("Sso"));
+                }
+            };
+            // 字段名取自页面, 见 challenge::detect
+            // 没有设置处理器时不作答直接提交, 识别有误也不会影响正常登录
+            let captcha = match challenge::detect(&page) {
+                Some(PageChallenge::Captcha { src, field }) if self.challenge.is_some() => Some((
+                    field.to_string(),
+                    self.answer_captcha(src, &page_url).await?,
+                )),
+                _ => None,
+            };
+            let mut form = vec![
+                ("username", un),
+                ("password", pw),
+                ("submit", "登录"),
+                ("type", "username_password"),
+                ("execution", &execution),
+                ("_eventId", "submit"),
+            ];
+            if let Some((field, answer)) = &captcha {
+                form.push((field, answer));
+            }
+            let res = self.post(&login_url).form(&form).send().await?;
+            // 只有状态码是有效信息
+            let status = res.status();
+            if status.is_success() {
+                self.cred.update(|c| c.refresh::<Sso>());
+                return Ok(LoginReport::LoggedIn);
+            }
+            page_url = res.url().clone();
+            let bytes = res.bytes();
+            // 当 "账号存在安全风险", "您的密码已过期或是已知的弱密码" 时尝试忽略风险继续登录
+            if contains(&bytes, b"continueForm") {
+                let warning = LoginWarning::from_page(&bytes);
+                if !self.auto_continue {
+                    return Err(Error::auth("Login stopped at a warning page")
+                        .with_label("Sso")
+                        .with_code(warning.code()));
+                }
+                log::warn!("SSO login continued over {warning:?}, suggest change password");
+                let execution = match utils::parse_by_tag(&bytes, "\"execution\" value=\"", "\"") {
+                    Some(s) => s,
+                    None => {
+                        return Err(Error::server(
+                            "Ignore the risk and continue login failed. No Execution Value",
+                        )
+                        .with_label("Sso"));
+                    }
+                };
+                let form = [("execution", execution), ("_eventId", "ignoreAndContinue")];
+                let res = self.post(&login_url).form(&form).send().await?;
+                if res.status().is_success() {
+                    self.cred.update(|c| c.refresh::<Sso>());
+                    return Ok(LoginReport::Continued(warning));
+                }
+            } else if let Some(PageChallenge::Sms { phone, field }) = challenge::detect(&bytes) {
+                self.answer_sms(&login_url, &bytes, phone, field).await?;
+                return Ok(LoginReport::LoggedIn);
+            } else if let Some(code) = failure_code(&bytes) {
+                return Err(Error::auth("Login refused by SSO")
+                    .with_label("Sso")
+                    .with_code(code)
+                    .with_source(format!("Response Code: {status}")));
+            } else if captcha.is_some()
+                && !wrong_password(&bytes)
+                && matches!(
+                    challenge::detect(&bytes),
+                    Some(PageChallenge::Captcha { .. })
+                )
+            {
+                // 只有确实提交了验证码, 且没有提示密码错误时才换一张重新作答
+                page = bytes;
+                continue;
             }
+            let mut source = format!("Response Code: {}", status);
+            if captcha.is_none() && challenge::detect(&bytes).is_some() {
+                source.push_str(". The page may ask for a captcha, set a challenge handler");
+            }
+            return Err(
+                Error::server("Login failed. Maybe wrong username or password")
+                    .with_label("Sso")
+                    .with_source(source),
+            );
+        }
+        Err(Error::auth("Login failed. Too many wrong captcha")
+            .with_label("Sso")
+            .with_code(Code::AuthChallenge))
+    }
+
+    // 把挑战交给调用方, 没有设置处理器或者放弃时登录失败
+    async fn answer(&self, challenge: LoginChallenge) -> crate::Result<String> {
+        let Some(handler) = &self.challenge else {
+            return Err(
+                Error::auth("Login requires a challenge, but no handler is set")
+                    .with_label("Sso")
+                    .with_code(Code::AuthChallenge),
+            );
         };
+        handler.answer(challenge).await.ok_or_else(|| {
+            Error::auth("Login challenge was not answered")
+                .with_label("Sso")
+                .with_code(Code::AuthChallenge)
+        })
+    }
+
+    async fn answer_captcha(&self, src: &str, page_url: &Url) -> crate::Result<String> {
+        let url = page_url
+            .join(src)
+            .map_err(|e| Error::server("Invalid captcha URL").with_source(e))?;
+        let image = self.get(url.as_str()).send().await?.bytes();
+        self.answer(LoginChallenge::Captcha { image }).await
+    }
+
+    // 二次验证页面出现时短信已经发出
+    async fn answer_sms(
+        &self,
+        login_url: &str,
+        page: &[u8],
+        phone: &str,
+        field: &str,
+    ) -> crate::Result<()> {
+        let execution =
+            utils::parse_by_tag(page, "\"execution\" value=\"", "\"").ok_or_else(|| {
+                Error::server("SMS verification failed. No Execution Value").with_label("Sso")
+            })?;
+        let phone = Some(phone.to_string());
+        let code = self.answer(LoginChallenge::Sms { phone }).await?;
         let form = [
-            ("username", un),
-            ("password", pw),
-            ("submit", "登录"),
-            ("type", "username_password"),
+            (field, code.as_str()),
             ("execution", execution),
             ("_eventId", "submit"),
         ];
-        let res = self.client.post(login_url).form(&form).send().await?;
-        // 只有状态码是有效信息
-        let status = res.status();
-        if status.is_success() {
-            cred.refresh::<Sso>();
+        let res = self.post(login_url).form(&form).send().await?;
+        if res.status().is_success() {
+            self.cred.update(|c| c.refresh::<Sso>());
             return Ok(());
         }
-        // 当 "账号存在安全风险", "您的密码已过期或是已知的弱密码" 时尝试忽略风险继续登录
-        let bytes = res.bytes().await?;
-        if bytes
-            .windows(b"continueForm".len())
-            .any(|w| w == b"continueForm")
-        {
-            trace!(
-                "SSO account has security risk, try to ignore and continue. Suggest change password."
-            );
-            let execution = match utils::parse_by_tag(&bytes, "\"execution\" value=\"", "\"") {
-                Some(s) => s,
-                None => {
-                    return Err(Error::server(
-                        "Ignore the risk and continue login failed. No Execution Value",
-                    )
-                    .with_label("Sso"));
-                }
-            };
-            let form = [("execution", execution), ("_eventId", "ignoreAndContinue")];
-            let res = self.client.post(login_url).form(&form).send().await?;
-            if res.status().is_success() {
-                cred.refresh::<Sso>();
-                return Ok(());
-            }
-        }
-        let source = format!("Response Code: {}", status);
-        Err(
-            Error::server("Login failed. Maybe wrong username or password")
-                .with_label("Sso")
-                .with_source(source),
-        )
+        Err(Error::auth("SMS verification failed. Maybe wrong code")
+            .with_label("Sso")
+            .with_code(Code::AuthChallenge))
     }
 }
@@ -0,0 +1,117 @@
+use bytes::Bytes;
+use futures::future::BoxFuture;
+
+use std::future::Future;
+
+/// A challenge raised by SSO during login, answered by a [`ChallengeHandler`]
+#[derive(Debug, Clone)]
+pub enum LoginChallenge {
+    /// Image CAPTCHA, answer with the characters in the image
+    Captcha {
+        /// Raw image bytes, usually JPEG or PNG
+        image: Bytes,
+    },
+    /// Second factor, a verification code has been sent by SMS. Answer with the code
+    Sms {
+        /// Masked phone number shown on the page, e.g. `138****0000`
+        phone: Option<String>,
+    },
+}
+
+/// Answer [`LoginChallenge`]s, set by [`crate::ContextBuilder::challenge`]
+///
+/// Implemented for async closures `Fn(LoginChallenge) -> impl Future<Output = Option<String>>`.
+/// Return `None` to give up, then the login fails with [`crate::error::Code::AuthChallenge`].
+///
+/// ## Example
+///
+/// ```no_run
+/// use buaa_api::ContextBuilder;
+/// use buaa_api::api::sso::LoginChallenge;
+///
+/// let context = ContextBuilder::new()
+///     .challenge(|challenge| async move {
+///         match challenge {
+///             LoginChallenge::Captcha { image } => {
+///                 std::fs::write("captcha.jpg", &image).ok()?;
+///                 println!("Enter the characters in captcha.jpg:");
+///             }
+///             LoginChallenge::Sms { phone } => println!("Enter the code sent to {phone:?}:"),
+///         }
+///         let mut answer = String::new();
+///         std::io::stdin().read_line(&mut answer).ok()?;
+///         Some(answer.trim().to_string())
+///     })
+///     .build();
+/// ```
+pub trait ChallengeHandler: Send + Sync + 'static {
+    /// Answer a challenge, `None` to give up
+    fn answer(&self, challenge: LoginChallenge) -> BoxFuture<'static, Option<String>>;
+}
+
+impl<F, Fut> ChallengeHandler for F
+where
+    F: Fn(LoginChallenge) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Option<String>> + Send + 'static,
+{
+    fn answer(&self, challenge: LoginChallenge) -> BoxFuture<'static, Option<String>> {
+        Box::pin(self(challenge))
+    }
+}
+
+// 登录页上需要作答的挑战
+pub(super) enum PageChallenge<'a> {
+    // 验证码图片的地址, 通常是相对路径
+    Captcha { src: &'a str, field: &'a str },
+    // 短信已发出, 页面上有脱敏的手机号
+    Sms { phone: &'a str, field: &'a str },
+}
+
+// 真实页面的字段名没有录制核对过, 因此不写死任何 name
+// 以验证码图片或者脱敏手机号作为标志, 作答字段取离标志最近的可填写输入框
+// 识别有误时代价很小: 没有处理器就不作答, 失败后也只在确实作答过时重试
+pub(super) fn detect(page: &[u8]) -> Option<PageChallenge<'_>> {
+    let page = std::str::from_utf8(page).ok()?;
+    if let Some(at) = masked_phone(page) {
+        return Some(PageChallenge::Sms {
+            phone: &page[at..at + 11],
+            field: answer_field(page, at)?,
+        });
+    }
+    // 只认 <img> 上的地址, 脚本和样式的路径里也可能出现 captcha
+    let (at, src) = page.match_indices("<img").find_map(|(i, _)| {
+        let tag = page[i..].split('>').next()?;
+        let src = attr(tag, "src")?;
+        src.contains("captcha").then_some((i, src))
+    })?;
+    Some(PageChallenge::Captcha {
+        src,
+        field: answer_field(page, at)?,
+    })
+}
+
+// 页面上的手机号一般形如 138****0000, 返回其位置
+fn masked_phone(page: &str) -> Option<usize> {
+    let is_digit = |b: &u8| b.is_ascii_digit();
+    page.as_bytes().windows(11).position(|w| {
+        w[..3].iter().all(is_digit) && w[3..7] == *b"****" && w[7..].iter().all(is_digit)
+    })
+}
+
+// 可填写的输入框: 文本类型, 不是用户名和密码, 取离 near 最近的一个
+fn answer_field(page: &str, near: usize) -> Option<&str> {
+    page.match_indices("<input")
+        .filter_map(|(i, _)| {
+            let tag = page[i..].split('>').next()?;
+            let name = attr(tag, "name")?;
+            let fillable = matches!(attr(tag, "type"), None | Some("text" | "number" | "tel"));
+            (fillable && name != "username" && name != "password")
+                .then_some((i.abs_diff(near), name))
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name)
+}
+
+fn attr<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    tag.split(&format!(" {name}=\"")).nth(1)?.split('"').next()
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use crate::request::Url;
+
+/// Outcome of a successful SSO login, see [`super::SsoApi::login_report`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginReport {
+    /// The SSO session was still valid, nothing was submitted
+    Valid,
+    /// Logged in with the password
+    LoggedIn,
+    /// Logged in after ignoring a warning page, the user should act on it
+    Continued(LoginWarning),
+    /// Another login running at the same time succeeded, its result is shared
+    Shared,
+}
+
+/// Warning page shown by SSO after a correct password
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginWarning {
+    /// "您的密码已过期或是已知的弱密码", the password should be changed
+    WeakPassword,
+    /// "账号存在安全风险"
+    AccountRisk,
+}
+
+impl LoginWarning {
+    // 警告页都带有 continueForm, 只能从文案区分
+    pub(super) fn from_page(page: &[u8]) -> Self {
+        let text = String::from_utf8_lossy(page);
+        if text.contains("弱密码") || text.contains("密码已过期") {
+            LoginWarning::WeakPassword
+        } else {
+            LoginWarning::AccountRisk
+        }
+    }
+
+    pub(super) fn code(self) -> crate::error::Code {
+        match self {
+            LoginWarning::WeakPassword => crate::error::Code::AuthWeakPassword,
+            LoginWarning::AccountRisk => crate::error::Code::AuthAccountRisk,
+        }
+    }
+}
+
+/// Result of [`super::SsoApi::service_login`]
+#[derive(Debug, Clone)]
+pub struct ServiceTicketResult {
+    /// URL after all redirects, usually a page of the service
+    pub final_url: Url,
+    /// URLs that redirected, in order, starting from the SSO login URL
+    pub redirects: Vec<Url>,
+    /// Query parameters seen after leaving SSO, e.g. `ticket` or `token`.
+    /// If a name appears more than once, the last one is kept
+    pub params: HashMap<String, String>,
+}
+
+impl ServiceTicketResult {
+    // SSO 自身的地址带有 service 参数, 不计入
+    pub(super) fn new(final_url: Url, redirects: Vec<Url>, sso: &str) -> Self {
+        let params = redirects
+            .iter()
+            .chain(std::iter::once(&final_url))
+            .filter(|u| !u.as_str().starts_with(sso))
+            .flat_map(|u| u.query_pairs().into_owned())
+            .collect();
+        ServiceTicketResult {
+            final_url,
+            redirects,
+            params,
+        }
+    }
+
+    /// Get a query parameter by name, see [`ServiceTicketResult::params`]
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.get(name).map(String::as_str)
+    }
+}
@@ -1,6 +1,13 @@
 //! # BUAA Single Sign On API
 
 mod auth;
+mod challenge;
+mod data;
+mod service;
+mod test;
+
+pub use challenge::{ChallengeHandler, LoginChallenge};
+pub use data::*;
 
 /// # BUAA SSO API
 ///
@@ -0,0 +1,43 @@
+use crate::api::Sso;
+use crate::error::Error;
+use crate::utils;
+
+use super::ServiceTicketResult;
+
+impl super::SsoApi {
+    /// # Login to a CAS Service
+    ///
+    /// Visit the SSO login page with `service`, SSO redirects back with a ticket and the
+    /// service sets up its own session in cookies. Log in to SSO first if it expired,
+    /// and once more if the server rejects a session that still looks valid locally.
+    ///
+    /// Use it to integrate other CAS-protected BUAA systems,
+    /// e.g. read a `token` from [`ServiceTicketResult::param`]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "sso.service_login",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
+    pub async fn service_login(&self, service: &str) -> crate::Result<ServiceTicketResult> {
+        self.relogin_once::<Sso, _, _>(|| self.service_login_once(service))
+            .await
+    }
+
+    async fn service_login_once(&self, service: &str) -> crate::Result<ServiceTicketResult> {
+        if self.cred.load().is_expired::<Sso>() {
+            self.login().await?;
+        }
+        let sso = &self.endpoints.sso;
+        let login_url = format!("{sso}/login?service={}", utils::url_encode(service));
+        let (res, redirects) = self.get(&login_url).send_traced().await?;
+        // 停在登录页说明 TGC 已在服务端失效, 交给 relogin_once 重新登录后再试一次
+        if res.url().as_str().starts_with(&format!("{sso}/login")) {
+            return Err(Error::expired("Sso").with_source(format!("Service: {service}")));
+        }
+        self.cred.update(|c| c.refresh::<Sso>());
+        Ok(ServiceTicketResult::new(res.url, redirects, sso))
+    }
+}
@@ -0,0 +1,46 @@
+#[cfg(test)]
+mod tests {
+    use crate::api::sso::challenge::{self, PageChallenge};
+
+    #[test]
+    fn test_detect_captcha() {
+        // 同一页面上还有短信登录的手机号输入框, 作答字段取离验证码图片最近的
+        let page = br#"<form id="fm1"><input type="text" name="username"/>
+            <input type="password" name="password"/>
+            <input id="code" type="text" name="authcode"/><img src="/sso/captcha.jpg?r=1"/>
+            <input type="hidden" name="execution" value="e1s1"/></form>
+            <form id="fm2"><input type="tel" name="mobile"/></form>"#;
+        match challenge::detect(page) {
+            Some(PageChallenge::Captcha { src, field }) => {
+                assert_eq!("/sso/captcha.jpg?r=1", src);
+                assert_eq!("authcode", field);
+            }
+            _ => panic!("captcha not detected"),
+        }
+
+        // 脚本路径里的 captcha 不算验证码图片
+        let page = br#"<script src="/sso/js/captcha.js"></script><form>
+            <input type="text" name="username"/><input type="password" name="password"/>
+            <input type="text" name="remark"/></form>"#;
+        assert!(challenge::detect(page).is_none());
+    }
+
+    #[test]
+    fn test_detect_sms() {
+        let page = "<p>验证码已发送至 138****0000</p><form>\
+            <input type=\"hidden\" name=\"execution\" value=\"e2s2\"/>\
+            <input type=\"text\" name=\"otp\"/></form>";
+        match challenge::detect(page.as_bytes()) {
+            Some(PageChallenge::Sms { phone, field }) => {
+                assert_eq!("138****0000", phone);
+                assert_eq!("otp", field);
+            }
+            _ => panic!("SMS not detected"),
+        }
+
+        // 密码错误重新渲染的登录页没有挑战, 即使有短信登录的输入框
+        let page = br#"<form><input type="text" name="username"/>
+            <input type="password" name="password"/><input type="tel" name="mobile"/></form>"#;
+        assert!(challenge::detect(page).is_none());
+    }
+}
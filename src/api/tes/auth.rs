@@ -1,32 +1,48 @@
+use bytes::Bytes;
+
 use crate::api::{Sso, Tes};
 use crate::error::Error;
+use crate::request::RequestBuilder;
 
 impl super::TesApi {
     /// Login to TesApi
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "tes.login",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn login(&self) -> crate::Result<()> {
-        let cred = self.cred.load();
-        if cred.is_expired::<Sso>() {
-            self.api::<Sso>().login().await?;
-        }
+        self.single_flight::<Tes>(self.login_once()).await
+    }
 
-        let url =
-            "https://sso.buaa.edu.cn/login?service=https%3A%2F%2Fspoc.buaa.edu.cn%2Fpjxt%2Fcas";
-        let res = self.client.get(url).send().await?;
-        if res.url().as_str() == url {
-            return Err(Error::server("Redirect failed").with_label("Tes"));
-        }
-        cred.refresh::<Sso>();
-        cred.refresh::<Tes>();
+    async fn login_once(&self) -> crate::Result<()> {
+        let service = format!("{}/pjxt/cas", self.endpoints.spoc);
+        self.api::<Sso>().service_login(&service).await?;
+        self.cred.update(|c| c.refresh::<Tes>());
         Ok(())
     }
 
-    // Tes API 足够简单, 数量不多, 无需通用请求, 否则会造成频繁检测刷新
-    // 而且返回内容也是一坨, 完全没有解析意义
-    pub(super) async fn refresh(&self) -> crate::Result<()> {
-        let cred = self.cred.load();
-        if cred.is_expired::<Tes>() {
-            self.login().await?;
-        }
-        Ok(())
+    // Tes API 足够简单, 数量不多, 只负责登录和重试, 不解析响应
+    // 返回内容也是一坨, 完全没有统一解析的意义
+    // 会话失效时被重定向到 SSO 登录页, 重新登录后重建请求再试一次
+    pub(super) async fn request_bytes<'a>(
+        &'a self,
+        req: impl Fn() -> RequestBuilder<'a, Tes>,
+    ) -> crate::Result<Bytes> {
+        let req = &req;
+        self.relogin_once::<Tes, _, _>(|| async move {
+            if self.cred.load().is_expired::<Tes>() {
+                self.login().await?;
+            }
+            let res = req().send().await?;
+            if self.is_rejected(&res) {
+                return Err(Error::expired("Tes"));
+            }
+            Ok(res.bytes())
+        })
+        .await
     }
 }
@@ -11,6 +11,7 @@
 mod auth;
 mod data;
 mod opt;
+mod test;
 
 pub use data::*;
 
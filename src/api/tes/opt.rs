@@ -1,20 +1,29 @@
 use futures::future;
 
 use crate::api::Data;
-use crate::error::Error;
+use crate::error::{Code, Error};
 use crate::utils;
 
 use super::{Completed, Form, Task};
 
 impl super::TesApi {
     /// # Get list of evaluation task
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "tes.get_task",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn get_task(&self) -> crate::Result<Vec<Task>> {
-        self.refresh().await?;
         let cred = self.cred.load();
         let username = cred.username()?;
         // 获取任务 ID
-        let url =
-            "https://spoc.buaa.edu.cn/pjxt/personnelEvaluation/listObtainPersonnelEvaluationTasks";
+        let url = format!(
+            "{}/pjxt/personnelEvaluation/listObtainPersonnelEvaluationTasks",
+            self.endpoints.spoc
+        );
         // 参数说明: 我们把所有能留空的参数留空, 默认查询当前学期未评任务
         // 页相关参数只有这里刚需, 否则报错
         // 任务名称是完全没用的参数, 完全可被学年学期取代
@@ -23,14 +32,7 @@ impl super::TesApi {
         // 这个参数在 `fetch_task` 中更刚需. 因为假如我们查询了所有学期所有任务, 而第一条不是当前学期,
         // 同时 `fetch_task` 没有设定查询那个指定学期, 则返回空
         let query = [("yhdm", username), ("pageNum", "1"), ("pageSize", "10")];
-        let res = self
-            .client
-            .get(url)
-            .query(&query)
-            .send()
-            .await?
-            .bytes()
-            .await?;
+        let res = self.request_bytes(|| self.get(&url).query(&query)).await?;
 
         let task_id = utils::parse_by_tag(&res, "\"rwid\":\"", "\"")
             .ok_or_else(|| Error::server("Empty task").with_label("Tes"))?;
@@ -41,16 +43,12 @@ impl super::TesApi {
 
         // 获取问卷 ID
         // 已知有五种问卷 ID: 理论课, 实践课, 英语课, 体育课, 科研课堂
-        let url = "https://spoc.buaa.edu.cn/pjxt/evaluationMethodSix/getQuestionnaireListToTask";
+        let url = format!(
+            "{}/pjxt/evaluationMethodSix/getQuestionnaireListToTask",
+            self.endpoints.spoc
+        );
         let query = [("rwid", task_id)];
-        let bytes = self
-            .client
-            .get(url)
-            .query(&query)
-            .send()
-            .await?
-            .bytes()
-            .await?;
+        let bytes = self.request_bytes(|| self.get(&url).query(&query)).await?;
 
         // 不解析这个结构, 直接循环匹配多个问卷 ID
         let left = "\"wjid\":\"";
@@ -83,31 +81,30 @@ impl super::TesApi {
     // 用于并发获取任务
     // 注意我们没必要在这里刷新权限因为它的调用者已经刷新了
     async fn fetch_task(&self, id: &str) -> crate::Result<Vec<Task>> {
-        let url = "https://spoc.buaa.edu.cn/pjxt/evaluationMethodSix/getRequiredReviewsData";
+        let url = format!(
+            "{}/pjxt/evaluationMethodSix/getRequiredReviewsData",
+            self.endpoints.spoc
+        );
         let query = [("wjid", id)];
-        let res = self
-            .client
-            .get(url)
-            .query(&query)
-            .send()
-            .await?
-            .bytes()
-            .await?;
+        let res = self.request_bytes(|| self.get(&url).query(&query)).await?;
         Ok(serde_json::from_slice::<Data<Vec<Task>>>(&res)?.0)
     }
 
     /// # Get the evaluation form
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "tes.get_form",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn get_form(&self, task: &Task) -> crate::Result<Form> {
-        self.refresh().await?;
-        let url = "https://spoc.buaa.edu.cn/pjxt/evaluationMethodSix/getQuestionnaireTopic";
-        let res = self
-            .client
-            .get(url)
-            .query(&task)
-            .send()
-            .await?
-            .bytes()
-            .await?;
+        let url = format!(
+            "{}/pjxt/evaluationMethodSix/getQuestionnaireTopic",
+            self.endpoints.spoc
+        );
+        let res = self.request_bytes(|| self.get(&url).query(&task)).await?;
         let res = serde_json::from_slice::<Data<Form>>(&res)?;
         Ok(res.0)
     }
@@ -125,32 +122,55 @@ impl super::TesApi {
     /// you can remove the 'disabled' attribute of the button in the browser console,
     /// and you'll be able to click it.
     /// Or you might wait a little longer, and it may return to normal.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "tes.submit_form",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn submit_form(&self, complete: Completed<'_>) -> crate::Result<()> {
-        self.refresh().await?;
-        let url = "https://spoc.buaa.edu.cn/pjxt/evaluationMethodSix/submitSaveEvaluation";
+        let url = format!(
+            "{}/pjxt/evaluationMethodSix/submitSaveEvaluation",
+            self.endpoints.spoc
+        );
         // TODO: 我们需要一个状态机来保证输入的必须是有效的类型, 而非在内部做一次判断
         if (complete.is_unqualified() || complete.is_perfect()) && complete.no_reason() {
             return Err(Error::parameter("No reason").with_label("Tes"));
         }
-        let res = self.client.post(url).json(&complete).send().await?;
+        let bytes = self
+            .request_bytes(|| self.post(&url).json(&complete))
+            .await?;
 
         let rwid = complete.rwid();
         let wjid = complete.wjid();
         // 也许是用于验证是否提交成功的
-        let url =
-            "https://spoc.buaa.edu.cn/pjxt/personnelEvaluation/checkWhetherTheTaskIsEvaluable";
+        let url = format!(
+            "{}/pjxt/personnelEvaluation/checkWhetherTheTaskIsEvaluable",
+            self.endpoints.spoc
+        );
         let query = [("rwid", rwid), ("wjid", wjid), ("sfyp", "0")];
-        self.client.post(url).query(&query).send().await?;
-        let url = "https://spoc.buaa.edu.cn/pjxt/system/property";
-        self.client.post(url).send().await?;
-        let bytes = res.bytes().await?;
+        self.request_bytes(|| self.post(&url).query(&query)).await?;
+        let url = format!("{}/pjxt/system/property", self.endpoints.spoc);
+        self.request_bytes(|| self.post(&url)).await?;
         let code = utils::parse_by_tag(&bytes, "\"code\":\"", "\"");
         if code == Some("200") {
-            Ok(())
-        } else {
-            Err(Error::server("Submit failed. No code")
-                .with_source(format!("Code: {:?}", code))
-                .with_label("Tes"))
+            return Ok(());
         }
+        let msg = utils::parse_by_tag(&bytes, "\"msg\":\"", "\"");
+        Err(submit_error(code, msg))
+    }
+}
+
+pub(super) fn submit_error(code: Option<&str>, msg: Option<&str>) -> Error {
+    let err = Error::server("Submit failed. No code")
+        .with_source(format!("Code: {:?}. Message: {:?}", code, msg))
+        .with_label("Tes");
+    // 重复提交时会提示已评价
+    if msg.is_some_and(|m| m.contains("已评")) {
+        err.with_code(Code::TesEvaluated)
+    } else {
+        err
     }
 }
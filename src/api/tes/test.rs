@@ -0,0 +1,15 @@
+#[cfg(test)]
+mod tests {
+    use crate::api::tes::opt::submit_error;
+    use crate::error::Code;
+
+    #[test]
+    fn test_submit_error() {
+        let err = submit_error(Some("500"), Some("该任务已评价, 请勿重复提交"));
+        assert_eq!(Some(&Code::TesEvaluated), err.code());
+
+        let err = submit_error(Some("500"), Some("系统异常"));
+        assert_eq!(None, err.code());
+        assert_eq!(None, submit_error(None, None).code());
+    }
+}
@@ -3,27 +3,35 @@ use crate::utils::time::DateTime;
 
 impl super::UserApi {
     /// # Login to UserApi
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "user.login",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn login(&self) -> crate::Result<()> {
-        let cred = self.cred.load();
-        if cred.is_expired::<Sso>() {
+        if self.cred.load().is_expired::<Sso>() {
             self.api::<Sso>().login().await?;
         }
-
         let time = DateTime::millis();
         // 获取 JSESSIONID
-        self.client
-            .get(format!(
-                "https://uc.buaa.edu.cn/api/uc/status?selfTimestamp={time}"
-            ))
-            .send()
-            .await?;
+        self.get(format!(
+            "{}/api/uc/status?selfTimestamp={time}",
+            self.endpoints.uc
+        ))
+        .send()
+        .await?;
         // 验证  JSESSIONID
         // 会经历 4 次重定向
-        self.client
-            .get("https://uc.buaa.edu.cn/api/login?target=https://uc.buaa.edu.cn/#/user/login")
-            .send()
-            .await?;
-        cred.refresh::<Sso>();
+        self.get(format!(
+            "{base}/api/login?target={base}/#/user/login",
+            base = self.endpoints.uc
+        ))
+        .send()
+        .await?;
+        self.cred.update(|c| c.refresh::<Sso>());
         Ok(())
     }
 }
@@ -3,17 +3,25 @@ use crate::utils::time::DateTime;
 impl super::UserApi {
     /// # Get User Center state
     /// - Output: `String`, JSON includes name and username, etc
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "user.get_state",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn get_state(&self) -> crate::Result<String> {
         let time = DateTime::millis();
         // 获取登录状态
         let res = self
-            .client
             .get(format!(
-                "https://uc.buaa.edu.cn/api/uc/status?selfTimestamp={time}"
+                "{}/api/uc/status?selfTimestamp={time}",
+                self.endpoints.uc
             ))
             .send()
             .await?;
-        let state = res.text().await?;
+        let state = res.text();
         Ok(state)
     }
 }
@@ -1,5 +1,3 @@
-use std::error::Error as StdError;
-
 use crate::crypto;
 use crate::error::{Code, Error};
 use crate::utils;
@@ -28,6 +26,14 @@ impl super::WifiApi {
     ///     wifi.login().await.unwrap();
     /// }
     /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "wifi.login",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn login(&self) -> crate::Result<()> {
         let cred = self.cred.load();
         let un = cred.username()?;
@@ -47,26 +53,8 @@ impl super::WifiApi {
         // 2025.04.11 更新
         // 这里会重定向到 index_1.html, 最终 url 在它的 html meta 标签里
         // 从那里获取接入点 AC ID
-        // 这里检查一下有无 DNS 错误, 如果有那证明我们没有连接到目标网络
-        let bytes = self
-            .client
-            .get("http://gw.buaa.edu.cn")
-            .send()
-            .await
-            .map_err(|e| {
-                let err = e
-                    .source()
-                    .and_then(|e1| e1.source())
-                    .map(|e2| e2)
-                    .unwrap_or_else(|| &e);
-                if err.to_string() == "dns error" {
-                    Error::network("DNS resolution failure").with_code(Code::NetworkDnsFailure)
-                } else {
-                    Error::network("From reqwest crate").with_source(e)
-                }
-            })?
-            .bytes()
-            .await?;
+        // 如果有 DNS 错误那证明我们没有连接到目标网络, 错误码为 NetworkDnsFailure
+        let bytes = self.get(&self.endpoints.gw_portal).send().await?.bytes();
 
         let ac_id = utils::parse_by_tag(&bytes, "ac_id=", "&")
             .ok_or_else(|| Error::server("No AC ID").with_label("Wifi"))?;
@@ -81,15 +69,14 @@ impl super::WifiApi {
             ("_", time),
         ];
         let res = self
-            .client
-            .get("https://gw.buaa.edu.cn/cgi-bin/get_challenge")
+            .get(format!("{}/cgi-bin/get_challenge", self.endpoints.gw))
             .query(&params)
             .send()
             .await?;
         if !res.status().is_success() {
             return Err(Error::server("Request for challenge value failed").with_label("Wifi"));
         };
-        let bytes = res.bytes().await?;
+        let bytes = res.bytes();
         let token = match utils::parse_by_tag(&bytes, "\"challenge\":\"", "\"") {
             Some(s) => s,
             None => return Err(Error::server("No challenge value").with_label("Wifi")),
@@ -139,12 +126,11 @@ impl super::WifiApi {
             ("_", time),
         ];
         let res = self
-            .client
-            .get("https://gw.buaa.edu.cn/cgi-bin/srun_portal")
+            .get(format!("{}/cgi-bin/srun_portal", self.endpoints.gw))
             .query(&params)
             .send()
             .await?;
-        let res = res.bytes().await?;
+        let res = res.bytes();
         // 注意没有考虑免费流量用尽或者全部流量用尽的情况
         // "ploy_msg":"您的免费30G流量已用尽，当前正在使用套餐流量。"
         if res.windows(CHECK.len()).any(|window| window == CHECK) {
@@ -176,6 +162,14 @@ impl super::WifiApi {
     ///     wifi.logout().await.unwrap();
     /// }
     /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "wifi.logout",
+            skip_all,
+            fields(url, status, retries, elapsed_ms, relogin)
+        )
+    )]
     pub async fn logout(&self) -> crate::Result<()> {
         let cred = self.cred.load();
         let un = cred.username()?;
@@ -194,26 +188,8 @@ impl super::WifiApi {
         // 2025.04.11 更新
         // 这里会重定向到 index_1.html, 最终 url 在它的 html meta 标签里
         // 从那里获取接入点 AC ID
-        // 这里检查一下有无 DNS 错误, 如果有那证明我们没有连接到目标网络
-        let bytes = self
-            .client
-            .get("http://gw.buaa.edu.cn")
-            .send()
-            .await
-            .map_err(|e| {
-                let err = e
-                    .source()
-                    .and_then(|e1| e1.source())
-                    .map(|e2| e2)
-                    .unwrap_or_else(|| &e);
-                if err.to_string() == "dns error" {
-                    Error::network("DNS resolution failure").with_code(Code::NetworkDnsFailure)
-                } else {
-                    Error::network("From reqwest crate").with_source(e)
-                }
-            })?
-            .bytes()
-            .await?;
+        // 如果有 DNS 错误那证明我们没有连接到目标网络, 错误码为 NetworkDnsFailure
+        let bytes = self.get(&self.endpoints.gw_portal).send().await?.bytes();
 
         let ac_id = utils::parse_by_tag(&bytes, "ac_id=", "&")
             .ok_or_else(|| Error::server("No AC ID").with_label("Wifi"))?;
@@ -232,13 +208,12 @@ impl super::WifiApi {
         ];
 
         let res = self
-            .client
-            .get("https://gw.buaa.edu.cn/cgi-bin/srun_portal")
+            .get(format!("{}/cgi-bin/srun_portal", self.endpoints.gw))
             .query(&params)
             .send()
             .await?;
 
-        let res = res.bytes().await?;
+        let res = res.bytes();
         if res.windows(CHECK.len()).any(|window| window == CHECK) {
             Ok(())
         } else {
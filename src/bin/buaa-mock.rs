@@ -0,0 +1,30 @@
+//! Mock BUAA server
+//!
+//! Usage: `buaa-mock [ADDR] [USERNAME] [PASSWORD]`, default `127.0.0.1:8080 mock mock`
+
+use buaa_api::mock::MockServer;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let addr = args.next().unwrap_or_else(|| "127.0.0.1:8080".to_string());
+
+    let server = match MockServer::start(&addr) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Failed to bind {addr}: {e}");
+            std::process::exit(1);
+        }
+    };
+    if let (Some(username), Some(password)) = (args.next(), args.next()) {
+        server.set_account(&username, &password);
+    }
+
+    println!("BUAA mock server listening on {}", server.base_url());
+    println!("{:#?}", server.endpoints());
+    println!("Boya requests need `ContextBuilder::boya_rsa_key(MOCK_BOYA_RSA_KEY)`");
+
+    // 服务运行在后台线程, 主线程只需保持存活
+    loop {
+        std::thread::park();
+    }
+}
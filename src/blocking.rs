@@ -0,0 +1,382 @@
+//! Blocking API facade for synchronous applications
+//!
+//! [`Context`] wraps the async [`crate::Context`] together with a single-threaded runtime,
+//! every API group method blocks the current thread until it completes, like `reqwest::blocking`.
+//!
+//! **Note**: Do not call these methods inside an async runtime, it panics.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use buaa_api::blocking::Context;
+//!
+//! let context = Context::new();
+//! context.set_account("username", "password");
+//! context.login().unwrap();
+//! let courses = context.boya().query_courses(1, 10).unwrap();
+//! ```
+
+use bytes::Bytes;
+use reqwest::Method;
+use serde::Serialize;
+use tokio::runtime::Runtime;
+
+use std::ops::Deref;
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+use std::path::Path;
+
+use crate::api::{
+    Aas, App, Boya, Class, Cloud, Live, Payload, Spoc, Srs, Sso, Tes, User, aas, app, boya, class,
+    cloud, live, spoc, srs, sso, tes,
+};
+use crate::utils::time::{DateTime, Week};
+
+/// Blocking version of [`crate::Context`]
+///
+/// Synchronous methods like [`crate::Context::set_account`] are available through `Deref`
+pub struct Context {
+    inner: crate::Context,
+    rt: Runtime,
+}
+
+impl Context {
+    /// Initialize the `Context`, see [`crate::Context::new`]
+    pub fn new() -> Context {
+        Self::from(crate::Context::new())
+    }
+
+    /// Initialize with authentication data from specified directory, see [`crate::Context::with_auth`]
+    #[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+    pub fn with_auth<P: AsRef<Path>>(dir: P) -> crate::Result<Self> {
+        crate::Context::with_auth(dir).map(Self::from)
+    }
+
+    /// Get the inner async context
+    pub fn inner(&self) -> &crate::Context {
+        &self.inner
+    }
+
+    /// Login to SSO, see [`crate::Context::login`]
+    pub fn login(&self) -> crate::Result<()> {
+        self.rt.block_on(self.inner.login())
+    }
+
+    const fn group<G>(&self) -> Api<'_, G> {
+        Api {
+            api: self.inner.api::<G>(),
+            rt: &self.rt,
+        }
+    }
+
+    /// Get BUAA Academic Affairs System API Group
+    pub const fn aas(&self) -> Api<'_, Aas> {
+        self.group()
+    }
+    /// Get BUAA App API Group
+    pub const fn app(&self) -> Api<'_, App> {
+        self.group()
+    }
+    /// Get BUAA Boya Course API Group
+    pub const fn boya(&self) -> Api<'_, Boya> {
+        self.group()
+    }
+    /// Get BUAA Smart Classroom API Group
+    pub const fn class(&self) -> Api<'_, Class> {
+        self.group()
+    }
+    /// Get BUAA Cloud Disk API Group
+    pub const fn cloud(&self) -> Api<'_, Cloud> {
+        self.group()
+    }
+    /// Get BUAA Classroom Live Broadcast (Spoc) API Group
+    pub const fn live(&self) -> Api<'_, Live> {
+        self.group()
+    }
+    /// Get BUAA Spoc Platform API Group
+    pub const fn spoc(&self) -> Api<'_, Spoc> {
+        self.group()
+    }
+    /// Get BUAA User Center API Group
+    pub const fn user(&self) -> Api<'_, User> {
+        self.group()
+    }
+    /// Get BUAA Undergraduate & Graduate Student Course Registration System API Group
+    pub const fn srs(&self) -> Api<'_, Srs> {
+        self.group()
+    }
+    /// Get BUAA SSO API Group
+    pub const fn sso(&self) -> Api<'_, Sso> {
+        self.group()
+    }
+    /// Get BUAA Teaching Evaluation System API Group
+    pub const fn tes(&self) -> Api<'_, Tes> {
+        self.group()
+    }
+    /// Get BUAA WiFi API Group
+    #[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+    pub const fn wifi(&self) -> Api<'_, crate::api::Wifi> {
+        self.group()
+    }
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<crate::Context> for Context {
+    fn from(inner: crate::Context) -> Self {
+        // 单线程运行时足够, 请求都由调用线程驱动
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("Failed to build blocking runtime");
+        Context { inner, rt }
+    }
+}
+
+impl Deref for Context {
+    type Target = crate::Context;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+/// Blocking view of an API group, obtained from [`Context`]
+pub struct Api<'a, G> {
+    api: &'a crate::Context<G>,
+    rt: &'a Runtime,
+}
+
+impl<G> Api<'_, G> {
+    /// Get the async API group
+    pub fn inner(&self) -> &crate::Context<G> {
+        self.api
+    }
+}
+
+// 为每个异步方法生成同名的阻塞方法, 带泛型参数的方法在下面手写
+macro_rules! blocking {
+    ($group:ty, $module:ident :: $alias:ident {
+        $( fn $name:ident $(<$lt:lifetime>)? ( $( $arg:ident : $ty:ty ),* ) -> $ret:ty; )*
+    }) => {
+        impl Api<'_, $group> {
+            $(
+                #[doc = concat!(
+                    "Blocking version of [`crate::api::", stringify!($module), "::",
+                    stringify!($alias), "::", stringify!($name), "`]"
+                )]
+                pub fn $name $(<$lt>)? (&self, $( $arg: $ty ),*) -> crate::Result<$ret> {
+                    self.rt.block_on(self.api.$name($( $arg ),*))
+                }
+            )*
+        }
+    };
+}
+
+blocking!(Aas, aas::AasApi {
+    fn login() -> ();
+    fn get_config() -> aas::Config;
+    fn query_week_schedule(config: &aas::Config) -> aas::Schedules;
+    fn query_term_schedule(config: &aas::Config) -> aas::Schedules;
+});
+
+blocking!(App, app::AppApi {
+    fn login() -> ();
+    fn universal_request(url: &str) -> Bytes;
+    fn get_exam() -> app::Exams;
+});
+
+blocking!(Boya, boya::BoyaApi {
+    fn login() -> ();
+    fn get_semester() -> boya::Semester;
+    fn query_courses(page: u8, size: u8) -> Vec<boya::Course>;
+    fn query_course(id: u32) -> boya::Course;
+    fn query_selected(semester: boya::Semester) -> Vec<boya::Selected>;
+    fn query_statistic() -> boya::Statistic;
+    fn select_course(id: u32) -> ();
+    fn drop_course(id: u32) -> ();
+    fn checkin_course(id: u32, c: &boya::Coordinate) -> boya::SignInfo;
+    fn checkout_course(id: u32, c: &boya::Coordinate) -> boya::SignInfo;
+});
+
+blocking!(Class, class::ClassApi {
+    fn login() -> ();
+    fn query_schedule(date: &DateTime) -> Vec<class::Schedule>;
+    fn query_course(id: &str) -> Vec<class::Course>;
+    fn query_course_schedule(id: &str) -> Vec<class::CourseSchedule>;
+    fn checkin(id: &str) -> ();
+});
+
+blocking!(Cloud, cloud::CloudApi {
+    fn login() -> ();
+    fn get_root_dir(root: cloud::Root) -> Vec<cloud::RootDir>;
+    fn get_user_dir() -> cloud::Item;
+    fn list_dir(item: &cloud::Item) -> cloud::Dir;
+    fn get_item_size(item: &cloud::Item) -> cloud::Size;
+    fn get_suggest_name(parent: &cloud::Item, name: &str) -> String;
+    fn create_dir(parent: &cloud::Item, name: &str) -> String;
+    fn rename_item(item: &cloud::Item, name: &str) -> ();
+    fn move_item(from: &cloud::Item, to: &cloud::Item) -> String;
+    fn copy_item(from: &cloud::Item, to: &cloud::Item) -> String;
+    fn delete_item(item: &cloud::Item) -> ();
+    fn list_recycle() -> cloud::Dir;
+    fn delete_recycle_item(item: &cloud::Item) -> ();
+    fn restore_recycle_item(item: &cloud::Item) -> String;
+    fn share_history() -> Vec<cloud::Share>;
+    fn share_record(item: &cloud::Item) -> Vec<cloud::Share>;
+    fn share_item(share: cloud::Share) -> cloud::Share;
+    fn share_update(share: &cloud::Share) -> ();
+    fn share_delete(share: &cloud::Share) -> ();
+    fn share_parse(id: &str, pwd: Option<&str>) -> cloud::Item;
+    fn get_download_url(item: &cloud::Item) -> String;
+    fn get_batch_url(items: &[&cloud::Item], name: &str) -> String;
+    fn upload_fast_check(args: &cloud::UploadArgs) -> bool;
+    fn upload_fast(args: &cloud::UploadArgs) -> ();
+    fn upload_small(args: &cloud::UploadArgs, body: Vec<u8>) -> ();
+});
+
+blocking!(Live, live::LiveApi {
+    fn login() -> ();
+    fn get_week_schedule(week: &Week) -> [Vec<live::Schedule>; 7];
+});
+
+blocking!(Spoc, spoc::SpocApi {
+    fn login() -> ();
+    fn get_week() -> spoc::Week;
+    fn query_week_schedules(week: &spoc::Week) -> Vec<spoc::Schedule>;
+    fn query_courses(term: &str) -> Vec<spoc::Course>;
+    fn query_homeworks(course: &spoc::Course) -> Vec<spoc::Homework>;
+    fn query_homework_detail(hw: &spoc::Homework) -> spoc::HomeworkDetail;
+    fn submit_homework(hw: &spoc::Homework, file: &spoc::UploadRes) -> ();
+});
+
+blocking!(Srs, srs::SrsApi {
+    fn login() -> ();
+    fn get_config() -> srs::Config;
+    fn get_batch() -> String;
+    fn query_course(filter: &srs::Filter) -> Vec<srs::Course>;
+    fn query_pre_selected() -> Vec<Vec<srs::Selected>>;
+    fn query_selected() -> Vec<srs::Selected>;
+    fn pre_select_course<'a>(opt: &'a srs::Opt<'a>) -> ();
+    fn select_course<'a>(opt: &'a srs::Opt<'a>) -> ();
+    fn drop_course<'a>(opt: &'a srs::Opt<'a>) -> ();
+});
+
+blocking!(Sso, sso::SsoApi {
+    fn login() -> ();
+    fn login_report() -> sso::LoginReport;
+    fn logout() -> ();
+    fn service_login(service: &str) -> sso::ServiceTicketResult;
+});
+
+blocking!(Tes, tes::TesApi {
+    fn login() -> ();
+    fn get_task() -> Vec<tes::Task>;
+    fn get_form(task: &tes::Task) -> tes::Form;
+    fn submit_form(complete: tes::Completed<'_>) -> ();
+});
+
+blocking!(User, user::UserApi {
+    fn login() -> ();
+    fn get_state() -> String;
+});
+
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+blocking!(crate::api::Wifi, wifi::WifiApi {
+    fn login() -> ();
+    fn logout() -> ();
+});
+
+impl Api<'_, Aas> {
+    /// Blocking version of [`crate::api::aas::AasApi::universal_request`]
+    pub fn universal_request<P>(
+        &self,
+        url: &str,
+        method: Method,
+        payload: &P,
+    ) -> crate::Result<Bytes>
+    where
+        P: Serialize + ?Sized,
+    {
+        self.rt
+            .block_on(self.api.universal_request(url, method, payload))
+    }
+}
+
+impl Api<'_, Boya> {
+    /// Blocking version of [`crate::api::boya::BoyaApi::universal_request`]
+    pub fn universal_request<P>(&self, url: &str, payload: &P) -> crate::Result<Bytes>
+    where
+        P: Serialize + ?Sized,
+    {
+        self.rt.block_on(self.api.universal_request(url, payload))
+    }
+}
+
+impl Api<'_, Cloud> {
+    /// Blocking version of [`crate::api::cloud::CloudApi::upload_big`]
+    pub fn upload_big<R>(&self, args: &cloud::UploadArgs, reader: R) -> crate::Result<()>
+    where
+        R: std::io::Read + Send + 'static,
+    {
+        self.rt.block_on(self.api.upload_big(args, reader))
+    }
+}
+
+impl Api<'_, Live> {
+    /// Blocking version of [`crate::api::live::LiveApi::universal_request`]
+    pub fn universal_request<P>(
+        &self,
+        url: &str,
+        method: Method,
+        payload: Payload<'_, P>,
+    ) -> crate::Result<Bytes>
+    where
+        P: Serialize + ?Sized,
+    {
+        self.rt
+            .block_on(self.api.universal_request(url, method, payload))
+    }
+}
+
+impl Api<'_, Spoc> {
+    /// Blocking version of [`crate::api::spoc::SpocApi::universal_request`]
+    pub fn universal_request<P>(
+        &self,
+        url: &str,
+        method: Method,
+        payload: Payload<'_, P>,
+    ) -> crate::Result<Bytes>
+    where
+        P: Serialize + ?Sized,
+    {
+        self.rt
+            .block_on(self.api.universal_request(url, method, payload))
+    }
+
+    /// Blocking version of [`crate::api::spoc::SpocApi::upload_callback`]
+    pub fn upload_callback<R, F>(
+        &self,
+        args: &spoc::UploadArgs,
+        reader: R,
+        progress: F,
+    ) -> crate::Result<spoc::UploadRes>
+    where
+        R: std::io::Read,
+        F: Fn(spoc::UploadProgress),
+    {
+        self.rt
+            .block_on(self.api.upload_callback(args, reader, progress))
+    }
+
+    /// Blocking version of [`crate::api::spoc::SpocApi::upload`]
+    pub fn upload<R>(&self, args: &spoc::UploadArgs, reader: R) -> crate::Result<spoc::UploadRes>
+    where
+        R: std::io::Read,
+    {
+        self.rt.block_on(self.api.upload(args, reader))
+    }
+}
@@ -1,110 +1,76 @@
-use std::cell::UnsafeCell;
-use std::marker::PhantomData;
-use std::sync::Mutex;
-use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::{Arc, RwLock};
 
-/// A conditional thread-safe cell optimized for frequent reads, some guarded updates, and rare atomic replacements.
+/// A thread-safe cell optimized for frequent reads, some guarded updates, and rare replacements.
 ///
 /// # Thread Safety Model
 ///
-/// - **Lock-Free Reads** (`load`): Always thread-safe.
-/// - **Mutex-Guarded Updates** (`update`): Thread-safe for concurrent access.
-/// - **Unsafe Atomic Stores** (`store`): Not thread-safe; caller must ensure no concurrent calls.
+/// - **Snapshot Reads** (`load`): Clone the current `Arc`. A snapshot stays valid after later writes.
+/// - **Copy-on-Write Updates** (`update`): Modify in place if no snapshot is alive, otherwise clone first.
+/// - **Atomic Replacements** (`store`): Swap in a new value. Old snapshots keep the old value alive.
 ///
 /// # Design
 ///
-/// - Use `load()` for high-frequency reads (no locks).
-/// - Use `update()` for safe in-place modifications (internal mutex).
-/// - Avoid `store()` unless absolutely needed (it bypasses the update mutex).
+/// - The lock is only held to clone or swap the `Arc`, never across an `await`.
+/// - A snapshot does not observe later writes. Load again after an operation that may write (e.g. login).
 ///
 /// # Example
 ///
-/// ```
-/// #[derive(Default)]
+/// ```ignore
+/// #[derive(Clone, Default)]
 /// pub struct Foo {
 ///    pub bar: String,
 /// }
 ///
 /// let cell = AtomicCell::new(Foo::default());
-/// // Lock-free read
-/// let cfg = cell.load();
-/// // Field-level update
-/// cell.update(|cfg| cfg.bar = "new".to_string());
-/// // Full replacement (must not race with update)
-/// cell.store(Config::default());
+/// // Snapshot read
+/// let foo = cell.load();
+/// // Field-level update, `foo` still holds the old value
+/// cell.update(|foo| foo.bar = "new".to_string());
+/// // Full replacement, safe even with concurrent readers
+/// cell.store(Foo::default());
 /// ```
 pub struct AtomicCell<T> {
-    data: AtomicPtr<UnsafeCell<T>>,
-    // Only protects `update`, does not block `load` or `store`.
-    update_lock: Mutex<()>,
-    _marker: PhantomData<T>,
+    data: RwLock<Arc<T>>,
 }
 
-unsafe impl<T: Send + Sync> Send for AtomicCell<T> {}
-unsafe impl<T: Send + Sync> Sync for AtomicCell<T> {}
-
 impl<T> AtomicCell<T>
 where
-    T: Send + Sync + 'static,
+    T: Clone + Send + Sync + 'static,
 {
     /// Create a new `AtomicCell` containing the given value.
     pub fn new(value: T) -> Self {
-        let cell = Box::new(UnsafeCell::new(value));
         Self {
-            data: AtomicPtr::new(Box::into_raw(cell)),
-            update_lock: Mutex::new(()),
-            _marker: PhantomData,
+            data: RwLock::new(Arc::new(value)),
         }
     }
 
-    /// Lock-free read access to the contained value.
-    ///
-    /// # Safety
-    ///
-    /// The returned reference is valid as long as there is no concurrent `store`.
-    pub fn load(&self) -> &T {
-        let ptr = self.data.load(Ordering::Acquire);
-        unsafe { &*(*ptr).get() }
+    /// Get a snapshot of the contained value.
+    pub fn load(&self) -> Arc<T> {
+        // 持锁期间不会 panic, 中毒也不影响数据一致性
+        let data = self.data.read().unwrap_or_else(|e| e.into_inner());
+        data.clone()
     }
 
     /// Replace the current value atomically.
     ///
-    /// # Safety
-    ///
-    /// This method is **not thread-safe by default**. Caller must ensure:
-    ///
-    /// - No concurrent calls to `store()`.
-    /// - No outstanding references from `load()` when called.
+    /// Snapshots from `load()` are not affected and keep the old value.
     pub fn store(&self, new_value: T) {
-        let cell = Box::new(UnsafeCell::new(new_value));
-        let new_ptr = Box::into_raw(cell);
-        let old_ptr = self.data.swap(new_ptr, Ordering::Release);
-        unsafe {
-            drop(Box::from_raw(old_ptr));
-        }
+        let old = {
+            let mut data = self.data.write().unwrap_or_else(|e| e.into_inner());
+            std::mem::replace(&mut *data, Arc::new(new_value))
+        };
+        // 在锁外释放旧值
+        drop(old);
     }
 
-    /// Update the value in-place with mutex protection.
+    /// Update the value with copy-on-write.
     ///
     /// This is safe for concurrent use but will block if another thread is updating.
-    /// ```
     pub fn update<F>(&self, updater: F)
     where
         F: FnOnce(&mut T),
     {
-        let _guard = self.update_lock.lock();
-        let ptr = self.data.load(Ordering::Acquire);
-        updater(unsafe { &mut *(*ptr).get() });
-    }
-}
-
-impl<T> Drop for AtomicCell<T> {
-    fn drop(&mut self) {
-        let ptr = self.data.load(Ordering::Relaxed);
-        if !ptr.is_null() {
-            unsafe {
-                drop(Box::from_raw(ptr));
-            }
-        }
+        let mut data = self.data.write().unwrap_or_else(|e| e.into_inner());
+        updater(Arc::make_mut(&mut data));
     }
 }
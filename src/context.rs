@@ -1,28 +1,64 @@
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+use futures::future::BoxFuture;
+use reqwest::cookie::CookieStore as _;
+use reqwest::header::{AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, COOKIE, LOCATION, SET_COOKIE};
+
+use std::collections::HashMap;
 use std::marker::PhantomData;
-use std::path::Path;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 
-use crate::request::{Client, client};
+use crate::error::{Error, Kind};
+use crate::request::{
+    Client, Layer, Layers, Method, RecordTransport, ReplayTransport, Request, RequestBuilder,
+    ReqwestTransport, Response, RetryPolicies, RetryPolicy, Scope, StatusCode, Transport, Url,
+    group_name, sleep, trace,
+};
 use crate::store::cookies::{AtomicCookieStore, CookieStore};
-use crate::store::cred::CredentialStore;
-use crate::{api::Core, cell::AtomicCell};
+use crate::store::cred::{CredentialItem, CredentialStore, Token};
+use crate::store::session::SessionReport;
+use crate::store::storage::AuthStorage;
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+use crate::store::storage::FileStorage;
+use crate::{
+    Endpoints,
+    api::sso::ChallengeHandler,
+    api::{Core, Sso},
+    cell::AtomicCell,
+};
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+use crate::{KeepAlive, KeepAliveHandle, keepalive};
 
 /// This is the core of this crate, it is used to store cookies and send requests
+///
+/// It is `Send + Sync`, share it with `Arc<Context>` across tasks
+// repr(C) 保证不同 G 的实例内存布局一致, 见 `api` 方法
+#[repr(C)]
 pub struct Context<G = Core> {
-    pub(crate) client: Client,
+    pub(crate) transport: Arc<dyn Transport>,
     pub(crate) cookies: Arc<AtomicCookieStore>,
     pub(crate) cred: AtomicCell<CredentialStore>,
+    pub(crate) endpoints: Endpoints,
+    pub(crate) retry: RetryPolicies,
+    pub(crate) flights: LoginFlights,
+    pub(crate) layers: Layers,
+    pub(crate) storage: Option<Arc<dyn AuthStorage>>,
+    pub(crate) autosave: bool,
+    pub(crate) challenge: Option<Arc<dyn ChallengeHandler>>,
+    pub(crate) auto_continue: bool,
+    pub(crate) boya_rsa_key: Option<String>,
     _marker: PhantomData<G>,
 }
 
-unsafe impl<G> Sync for Context<G> {}
-
 impl<G> std::fmt::Debug for Context<G> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Context")
-            .field("client", &"<client>")
+            .field("transport", &"<transport>")
             .field("cookies", &"<cookies>")
             .field("cred", &"<credentials>")
+            .field("endpoints", &self.endpoints)
+            .field("retry", &self.retry)
             .field("_marker", &self._marker)
             .finish()
     }
@@ -58,11 +94,7 @@ impl Context {
     /// If either file doesn't exist or fails to load, default values will be used instead.
     #[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
     pub fn with_auth<P: AsRef<Path>>(dir: P) -> crate::Result<Self> {
-        let cookies_path = dir.as_ref().join("cookies.json");
-        let cookies = CookieStore::from_file(cookies_path)?;
-        let cred_path = dir.as_ref().join("cred.json");
-        let cred = CredentialStore::from_file(cred_path)?;
-
+        let (cookies, cred) = FileStorage::new(dir).load()?;
         Ok(ContextBuilder::new().cookies(cookies).cred(cred).build())
     }
 
@@ -121,30 +153,56 @@ impl Context {
     }
 
     /// Set cookies
+    ///
+    /// Safe to call while other tasks are sending requests, e.g. to switch account.
+    /// In-flight requests keep using the snapshot they already loaded
     pub fn set_cookies(&self, cookies: CookieStore) {
-        // 这在理论上是安全的
-        // 因为没有理由在多线程中频繁切换 cookie
-        // 如果有并发需求应该创建多个 Context 而不是切换这个
-        // 它唯一的作用就是用于切换账号. 这能保证不会有卡在中间执行的请求
-        // 其他 load 方法的生命周期局限在自己的函数中
         self.cookies.store(cookies);
     }
 
     /// Set credentials
+    ///
+    /// Safe to call while other tasks are sending requests, see [`Context::set_cookies`]
     pub fn set_cred(&self, cred: CredentialStore) {
         self.cred.store(cred);
     }
 
-    /// Get cookies
-    pub fn get_cookies(&self) -> &CookieStore {
-        &self.cookies.load()
+    /// Get a snapshot of cookies, it does not observe later changes
+    pub fn get_cookies(&self) -> Arc<CookieStore> {
+        self.cookies.load()
     }
 
-    /// Get credentials
-    pub fn get_cred(&self) -> &CredentialStore {
+    /// Get a snapshot of credentials, it does not observe later changes
+    pub fn get_cred(&self) -> Arc<CredentialStore> {
         self.cred.load()
     }
 
+    /// Report local status of every API group's credential, without sending any request
+    ///
+    /// Call [`SessionReport::verify`] to confirm with the servers
+    pub fn session_report(&self) -> SessionReport {
+        SessionReport::new(&self.cred.load())
+    }
+
+    /// Refresh sessions in the background shortly before they expire, see [`KeepAlive`]
+    ///
+    /// The task is runtime agnostic, pass a `spawn` that runs it, e.g. `tokio::spawn`.
+    /// It stops when [`KeepAliveHandle::stop`] is called or the handle is dropped
+    #[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+    pub fn spawn_keepalive<S>(self: &Arc<Self>, keepalive: KeepAlive, spawn: S) -> KeepAliveHandle
+    where
+        S: FnOnce(BoxFuture<'static, ()>),
+    {
+        let (handle, stop, running) = keepalive::handle();
+        spawn(Box::pin(keepalive::run(
+            self.clone(),
+            keepalive,
+            stop,
+            running,
+        )));
+        handle
+    }
+
     /// Load authentication data (credentials and cookies) from specified directory.
     ///
     /// This will attempt to load:
@@ -160,13 +218,7 @@ impl Context {
     /// ```
     #[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
     pub fn load_auth<P: AsRef<Path>>(&self, dir: P) -> crate::Result<()> {
-        let cookies_path = dir.as_ref().join("cookies.json");
-        let cookies = CookieStore::from_file(cookies_path)?;
-        let cred_path = dir.as_ref().join("cred.json");
-        let cred = CredentialStore::from_file(cred_path)?;
-        self.set_cookies(cookies);
-        self.set_cred(cred);
-        Ok(())
+        self.load_from(&FileStorage::new(dir))
     }
 
     /// Save authentication data (credentials and cookies) to specified directory.
@@ -182,12 +234,58 @@ impl Context {
     /// ```
     #[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
     pub fn save_auth<P: AsRef<Path>>(&self, dir: P) -> crate::Result<()> {
-        let cookies_path = dir.as_ref().join("cookies.json");
-        let cred_path = dir.as_ref().join("cred.json");
-        self.get_cookies().to_file(cookies_path)?;
-        self.get_cred().to_file(cred_path)?;
+        self.save_to(&FileStorage::new(dir))
+    }
+
+    /// Load authentication data from specified directory, with credentials encrypted by `passphrase`
+    ///
+    /// This will attempt to load:
+    /// - Cookies from `./dir/cookies.json`
+    /// - Credentials from `./dir/cred.enc`
+    #[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+    pub fn load_auth_encrypted<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        passphrase: &str,
+    ) -> crate::Result<()> {
+        self.load_from(&FileStorage::new(dir).passphrase(passphrase))
+    }
+
+    /// Save authentication data to specified directory, with credentials encrypted by `passphrase`
+    ///
+    /// Same as [`Context::save_auth`], but credentials are saved to `./dir/cred.enc`
+    /// instead of plaintext `./dir/cred.json`. See [`CredentialStore::to_file_encrypted`]
+    #[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+    pub fn save_auth_encrypted<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        passphrase: &str,
+    ) -> crate::Result<()> {
+        self.save_to(&FileStorage::new(dir).passphrase(passphrase))
+    }
+
+    /// Load authentication data from an [`AuthStorage`]
+    pub fn load_from<S: AuthStorage + ?Sized>(&self, storage: &S) -> crate::Result<()> {
+        let (cookies, cred) = storage.load()?;
+        self.set_cookies(cookies);
+        self.set_cred(cred);
         Ok(())
     }
+
+    /// Save authentication data to an [`AuthStorage`]
+    pub fn save_to<S: AuthStorage + ?Sized>(&self, storage: &S) -> crate::Result<()> {
+        storage.save(&self.get_cookies(), &self.get_cred())
+    }
+
+    /// Save authentication data to the storage set by [`ContextBuilder::storage`]
+    ///
+    /// Do nothing if no storage is set
+    pub fn save(&self) -> crate::Result<()> {
+        match &self.storage {
+            Some(storage) => self.save_to(&**storage),
+            None => Ok(()),
+        }
+    }
 }
 
 impl<G> crate::Context<G> {
@@ -200,8 +298,9 @@ impl<G> crate::Context<G> {
     ///
     /// The cast is safe because:
     ///
-    /// 1. `PhantomData<G>` has no runtime representation
-    /// 2. All context data is stored in `Arc`-wrapped fields
+    /// 1. `Context` is `#[repr(C)]` and `PhantomData<G>` is a zero-sized last field,
+    ///    so `Context<G>` and `Context<N>` have the same layout
+    /// 2. No field depends on `G`
     /// 3. The original context remains accessible
     #[inline]
     pub const fn api<N>(&self) -> &crate::Context<N> {
@@ -211,40 +310,369 @@ impl<G> crate::Context<G> {
         }
     }
 
-    /// Get inner HTTP client.
-    /// You can use this to make custom requests
-    pub fn client(&self) -> &Client {
-        &self.client
+    /// Get inner HTTP transport
+    pub fn transport(&self) -> &dyn Transport {
+        &*self.transport
+    }
+
+    /// Get inner HTTP client, `None` if a custom [`Transport`] without `reqwest` is used
+    ///
+    /// Requests sent by this client bypass the cookies and redirects of this context
+    #[deprecated(note = "use `Context::request` or `Context::transport` instead")]
+    pub fn client(&self) -> Option<&Client> {
+        self.transport.client()
+    }
+
+    /// Start a request through this context.
+    /// You can use this to make custom requests, cookies and redirects are handled automatically
+    pub fn request(&self, method: Method, url: &str) -> RequestBuilder<'_, G> {
+        RequestBuilder::new(self, method, url)
+    }
+
+    /// Start a `GET` request, see [`Context::request`]
+    pub fn get<U: AsRef<str>>(&self, url: U) -> RequestBuilder<'_, G> {
+        self.request(Method::GET, url.as_ref())
+    }
+
+    /// Start a `POST` request, see [`Context::request`]
+    pub fn post<U: AsRef<str>>(&self, url: U) -> RequestBuilder<'_, G> {
+        self.request(Method::POST, url.as_ref())
+    }
+
+    /// Start a `PUT` request, see [`Context::request`]
+    pub fn put<U: AsRef<str>>(&self, url: U) -> RequestBuilder<'_, G> {
+        self.request(Method::PUT, url.as_ref())
+    }
+
+    // 同一 token 的登录同时只进行一次
+    // 等锁期间如果已经有一次登录完成, 直接共享它的结果而不再登录
+    pub(crate) async fn single_flight<T: Token>(
+        &self,
+        login: impl Future<Output = crate::Result<()>>,
+    ) -> crate::Result<()> {
+        let flight = self.flights.get(T::NAME);
+        let seen = flight.generation.load(Ordering::Acquire);
+        let _guard = flight.lock.lock().await;
+        if flight.generation.load(Ordering::Acquire) != seen {
+            // 共享领头者的错误, 调用方仍能据错误码区分密码错误, 账号锁定等
+            return match &*flight.error.lock().unwrap_or_else(|e| e.into_inner()) {
+                Some(e) => Err(e.share()),
+                None => Ok(()),
+            };
+        }
+        let res = login.await;
+        *flight.error.lock().unwrap_or_else(|e| e.into_inner()) =
+            res.as_ref().err().map(Error::share);
+        flight.generation.fetch_add(1, Ordering::AcqRel);
+        match &res {
+            Ok(()) => {
+                self.hook(T::NAME, |l, s| l.on_login(s));
+                self.autosave();
+            }
+            Err(e) => self.hook(T::NAME, |l, s| l.on_error(s, e)),
+        }
+        res
+    }
+
+    // 登录成功后写回存储, 存储失败不影响登录结果
+    // 在登录路径上同步执行, FileStorage 缓存了派生密钥, 只剩一次小文件写入
+    fn autosave(&self) {
+        if !self.autosave {
+            return;
+        }
+        if let Some(storage) = &self.storage
+            && let Err(e) = storage.save(&self.cookies.load(), &self.cred.load())
+        {
+            log::warn!("Failed to save auth data: {e}");
+        }
+    }
+
+    // 没有 Layer 时直接跳过, 免得每个请求都加载一次凭据
+    pub(crate) fn hook(&self, group: &'static str, f: impl FnOnce(&Layers, &Scope)) {
+        if self.layers.is_empty() {
+            return;
+        }
+        let cred = self.cred.load();
+        let scope = Scope {
+            group,
+            username: cred.username.as_deref(),
+        };
+        f(&self.layers, &scope);
+    }
+
+    // 本地时效只是提前登录的提示, 以服务端的响应为准
+    // 凭据被拒绝时使 token 和 SSO 过期, 重新登录后再试一次
+    pub(crate) async fn relogin_once<T, R, F>(&self, f: impl Fn() -> F) -> crate::Result<R>
+    where
+        T: Token,
+        F: Future<Output = crate::Result<R>>,
+    {
+        // 记录发请求前的登录代数, 期间已有其他任务重新登录过时新 token 不能作废
+        let generation = self.flights.generation(T::NAME);
+        let sso_generation = self.flights.generation(Sso::NAME);
+        // 与 send_traced 一样装箱, 登录和请求都在 f 里, 嵌套最深
+        let res = match Box::pin(f()).await {
+            Err(e) if e.is_expired() => {
+                log::debug!("{} credential rejected by server, login again", T::NAME);
+                self.cred.update(|c| {
+                    if self.flights.generation(T::NAME) == generation {
+                        c.expire::<T>();
+                    }
+                    if self.flights.generation(Sso::NAME) == sso_generation {
+                        c.expire::<Sso>();
+                    }
+                });
+                trace::record_relogin(T::NAME);
+                Box::pin(f()).await
+            }
+            res => res,
+        };
+        // 网络错误已经在 send 中报告过
+        if let Err(e) = &res
+            && !matches!(e.kind(), Kind::Network)
+        {
+            self.hook(group_name::<G>(), |l, s| l.on_error(s, e));
+        }
+        res
+    }
+
+    // 按服务的 base URL 取 Cookie 的值, WebVPN 下所有 Cookie 都在门户域名下, 以路径区分服务
+    pub(crate) fn service_cookie(&self, base: &str, name: &str) -> Option<String> {
+        let base = Url::parse(base).ok()?;
+        self.cookies
+            .load()
+            .get_by_base(&base, name)
+            .and_then(|c| c.value())
+            .map(str::to_string)
+    }
+
+    // 清空 token 并删除其服务的 Cookie, 各 API 组的 reset 由 impl_token! 生成
+    pub(crate) fn reset_token<T: Token>(&self, bases: &[String]) {
+        self.cred
+            .update(|c| *T::mut_field(c) = CredentialItem::default());
+        self.cookies.update(|s| {
+            for url in bases.iter().filter_map(|b| Url::parse(b).ok()) {
+                s.remove_by_url(&url);
+            }
+        });
+    }
+
+    // 被重定向回 SSO 登录页或者 401 说明凭据已失效
+    pub(crate) fn is_rejected(&self, res: &Response) -> bool {
+        res.status == StatusCode::UNAUTHORIZED
+            || res
+                .url
+                .as_str()
+                .starts_with(&format!("{}/login", self.endpoints.sso))
+    }
+
+    // 对幂等请求按当前 API 组的策略重试, 非幂等请求只发送一次
+    pub(crate) async fn send(&self, req: Request, idempotent: bool) -> crate::Result<Response> {
+        self.send_traced(req, idempotent, &mut Vec::new()).await
+    }
+
+    // 同 send, 另外记录经过的每一跳重定向
+    pub(crate) async fn send_traced(
+        &self,
+        mut req: Request,
+        idempotent: bool,
+        hops: &mut Vec<Url>,
+    ) -> crate::Result<Response> {
+        let group = group_name::<G>();
+        self.hook(group, |l, s| l.on_request(s, &mut req));
+        // 装箱截断 future 的嵌套, 否则 API 方法层层 async 叠加后下游编译时会超出递归深度
+        let mut res = Box::pin(self.send_retry(req, idempotent, hops)).await;
+        match &mut res {
+            Ok(res) => self.hook(group, |l, s| l.on_response(s, res)),
+            Err(e) => self.hook(group, |l, s| l.on_error(s, e)),
+        }
+        res
+    }
+
+    async fn send_retry(
+        &self,
+        req: Request,
+        idempotent: bool,
+        hops: &mut Vec<Url>,
+    ) -> crate::Result<Response> {
+        let policy = self.retry.get::<G>();
+        let mut attempt = 1;
+        #[cfg(feature = "tracing")]
+        let start = trace::Instant::now();
+        loop {
+            hops.clear();
+            let res = self.execute(req.clone(), hops).await;
+            if !idempotent || !policy.allows(attempt) || !RetryPolicy::is_transient(&res) {
+                #[cfg(feature = "tracing")]
+                trace::record_request(&req.url, &res, attempt - 1, start);
+                return res;
+            }
+            let delay = policy.delay(attempt);
+            log::warn!(
+                "Transient failure on {} {}, retry {attempt} after {delay:?}",
+                req.method,
+                req.url
+            );
+            sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    // Transport 只负责单跳, Cookie 的收发和重定向在这里逐跳处理
+    pub(crate) async fn execute(
+        &self,
+        mut req: Request,
+        hops: &mut Vec<Url>,
+    ) -> crate::Result<Response> {
+        const MAX_REDIRECTS: usize = 10;
+        let mut redirects = 0;
+        loop {
+            req.headers.remove(COOKIE);
+            if let Some(cookie) = self.cookies.cookies(&req.url) {
+                req.headers.insert(COOKIE, cookie);
+            }
+            let res = self.transport.execute(req.clone()).await?;
+            let mut set_cookies = res.headers.get_all(SET_COOKIE).iter();
+            self.cookies.set_cookies(&mut set_cookies, &res.url);
+
+            if !res.status.is_redirection() {
+                return Ok(res);
+            }
+            let Some(location) = res.headers.get(LOCATION) else {
+                return Ok(res);
+            };
+            redirects += 1;
+            hops.push(res.url.clone());
+            if redirects > MAX_REDIRECTS {
+                return Err(Error::server("Too many redirects").with_source(res.url.to_string()));
+            }
+            let next = location
+                .to_str()
+                .ok()
+                .and_then(|l| res.url.join(l).ok())
+                .ok_or_else(|| Error::server("Invalid redirect location"))?;
+
+            // 与浏览器行为一致: 307/308 保留方法和请求体, 其余转为 GET
+            if !matches!(
+                res.status,
+                StatusCode::TEMPORARY_REDIRECT | StatusCode::PERMANENT_REDIRECT
+            ) && req.method != Method::HEAD
+            {
+                req.method = Method::GET;
+                req.body = None;
+                req.headers.remove(CONTENT_TYPE);
+                req.headers.remove(CONTENT_LENGTH);
+            }
+            // 跨域名时不带上授权头
+            if next.host_str() != req.url.host_str() {
+                req.headers.remove(AUTHORIZATION);
+            }
+            req.url = next;
+        }
+    }
+
+    /// Get base URLs of BUAA services used by this context
+    pub fn endpoints(&self) -> &Endpoints {
+        &self.endpoints
+    }
+}
+
+// 每种 token 一个登录锁, 代数用于判断等待期间是否已有登录完成
+#[derive(Default)]
+struct Flight {
+    lock: futures::lock::Mutex<()>,
+    generation: AtomicU64,
+    // 最近一次登录的错误, 成功时为 None
+    error: Mutex<Option<Error>>,
+}
+
+#[derive(Default)]
+pub(crate) struct LoginFlights {
+    flights: Mutex<HashMap<&'static str, Arc<Flight>>>,
+}
+
+impl LoginFlights {
+    fn get(&self, name: &'static str) -> Arc<Flight> {
+        let mut flights = self.flights.lock().unwrap_or_else(|e| e.into_inner());
+        flights.entry(name).or_default().clone()
+    }
+
+    // 登录过的次数, 前后对比可知期间是否重新登录过
+    pub(crate) fn generation(&self, name: &'static str) -> u64 {
+        self.get(name).generation.load(Ordering::Acquire)
     }
 }
 
 /// Context builder
+#[derive(Clone)]
 pub struct ContextBuilder {
-    client: Option<Client>,
+    transport: Option<Arc<dyn Transport>>,
+    record: Option<PathBuf>,
     tls: bool,
     cookies: Option<CookieStore>,
     cred: Option<CredentialStore>,
+    endpoints: Option<Endpoints>,
+    webvpn: bool,
+    webvpn_portal: Option<String>,
+    retry: RetryPolicies,
+    layers: Layers,
+    storage: Option<Arc<dyn AuthStorage>>,
+    autosave: bool,
+    challenge: Option<Arc<dyn ChallengeHandler>>,
+    auto_continue: bool,
+    boya_rsa_key: Option<String>,
 }
 
 impl ContextBuilder {
     /// Create a new ContextBuilder
     pub fn new() -> Self {
         ContextBuilder {
-            client: None,
+            transport: None,
+            record: None,
             tls: true,
             cookies: None,
             cred: None,
+            endpoints: None,
+            webvpn: false,
+            webvpn_portal: None,
+            retry: RetryPolicies::default(),
+            layers: Layers::default(),
+            storage: None,
+            autosave: false,
+            challenge: None,
+            auto_continue: true,
+            boya_rsa_key: None,
         }
     }
     /// Set the HTTP client
     ///
-    /// Should enable cookies feature. And with UA below
+    /// Should disable redirect and cookie store. And with UA below
     ///
     /// `Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/130.0.0.0 Safari/537.36 Edg/130.0.0.0`
-    pub fn client(mut self, client: Client) -> Self {
-        self.client = Some(client);
+    pub fn client(self, client: Client) -> Self {
+        self.transport(ReqwestTransport::with_client(client))
+    }
+    /// Set the HTTP transport, e.g. an in-process fake for tests
+    ///
+    /// Cookies and redirects are still handled by `Context`, see [`Transport`]
+    pub fn transport<T: Transport>(mut self, transport: T) -> Self {
+        self.transport = Some(Arc::new(transport));
         self
     }
+    /// Record every request/response hop to fixture files in `dir`
+    ///
+    /// Wraps the transport set by [`ContextBuilder::transport`] or the default one.
+    /// See [`RecordTransport`]
+    pub fn record<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.record = Some(dir.as_ref().to_path_buf());
+        self
+    }
+    /// Serve responses from fixture files in `dir` without network
+    ///
+    /// The fixtures should be written by [`ContextBuilder::record`]. See [`ReplayTransport`]
+    pub fn replay<P: AsRef<Path>>(self, dir: P) -> Self {
+        self.transport(ReplayTransport::new(dir))
+    }
     /// Set the TLS configuration
     ///
     /// Sometimes the SSL certificate may be invalid, you can disable the verification
@@ -262,25 +690,141 @@ impl ContextBuilder {
         self.cred = Some(cred);
         self
     }
+    /// Set the [`AuthStorage`] of this context
+    ///
+    /// On build, cookies and credentials not set explicitly are loaded from it.
+    /// If loading fails (e.g. first run), default values are used
+    pub fn storage<S: AuthStorage>(mut self, storage: S) -> Self {
+        self.storage = Some(Arc::new(storage));
+        self
+    }
+    /// Save to the storage after every successful login or token update, default `false`
+    ///
+    /// Saving runs synchronously in the login path, an [`AuthStorage`] doing slow work
+    /// should hand it to its own thread. See [`ContextBuilder::storage`]
+    pub fn autosave(mut self, autosave: bool) -> Self {
+        self.autosave = autosave;
+        self
+    }
+    /// Set base URLs of BUAA services
+    ///
+    /// Use [`Endpoints::local`] to target a local mock server
+    pub fn endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = Some(endpoints);
+        self
+    }
+    /// Access campus services through WebVPN (`https://d.buaa.edu.cn`) for off-campus use
+    ///
+    /// The endpoints set by [`ContextBuilder::endpoints`] (or the default ones) are rewritten
+    /// into WebVPN form, see [`Endpoints::webvpn`]
+    pub fn webvpn(mut self, webvpn: bool) -> Self {
+        self.webvpn = webvpn;
+        self
+    }
+    /// Use another WebVPN portal instead of [`Endpoints::WEBVPN_PORTAL`]
+    ///
+    /// Only takes effect when [`ContextBuilder::webvpn`] is enabled
+    pub fn webvpn_portal(mut self, portal: &str) -> Self {
+        self.webvpn_portal = Some(portal.to_string());
+        self
+    }
+    /// Set the default retry policy for all API groups
+    ///
+    /// Use [`RetryPolicy::none`] to disable retry
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry.default = policy;
+        self
+    }
+    /// Override the retry policy for API group `G`, e.g. [`crate::api::Class`]
+    pub fn retry_for<G>(mut self, policy: RetryPolicy) -> Self {
+        self.retry.groups.insert(std::any::type_name::<G>(), policy);
+        self
+    }
+    /// Add a [`Layer`] to observe and modify traffic, layers are called in the order they are added
+    pub fn layer<L: Layer>(mut self, layer: L) -> Self {
+        self.layers.push(Arc::new(layer));
+        self
+    }
+    /// Answer captcha and SMS challenges raised by SSO login, see [`ChallengeHandler`]
+    ///
+    /// Without it, such a login fails with [`crate::error::Code::AuthChallenge`]
+    pub fn challenge<H: ChallengeHandler>(mut self, handler: H) -> Self {
+        self.challenge = Some(Arc::new(handler));
+        self
+    }
+    /// Whether SSO login clicks through warning pages like "weak password", default `true`
+    ///
+    /// When disabled, such a login fails with [`crate::error::Code::AuthWeakPassword`]
+    /// or [`crate::error::Code::AuthAccountRisk`]. See also [`crate::api::sso::SsoApi::login_report`]
+    pub fn auto_continue(mut self, auto_continue: bool) -> Self {
+        self.auto_continue = auto_continue;
+        self
+    }
+    /// Set the PEM public key used by Boya to encrypt the request AES key
+    ///
+    /// Only a mock server of Boya needs it, since it has its own key pair
+    pub fn boya_rsa_key(mut self, pem: impl Into<String>) -> Self {
+        self.boya_rsa_key = Some(pem.into());
+        self
+    }
+    // 提前创建默认 Transport, 克隆出的构建器共用同一个连接池
+    // 存储是每个账号独有的, 不能共享
+    pub(crate) fn share_transport(mut self) -> Self {
+        if self.transport.is_none() {
+            self.transport = Some(Arc::new(ReqwestTransport::new(self.tls)));
+        }
+        self.storage = None;
+        self
+    }
     /// Build the Context
-    pub fn build(self) -> Context {
+    pub fn build(mut self) -> Context {
+        if let Some(storage) = &self.storage
+            && (self.cookies.is_none() || self.cred.is_none())
+        {
+            match storage.load() {
+                Ok((cookies, cred)) => {
+                    self.cookies.get_or_insert(cookies);
+                    self.cred.get_or_insert(cred);
+                }
+                Err(e) => log::debug!("No auth data loaded from storage: {e}"),
+            }
+        }
         let cookies = self
             .cookies
             .map(AtomicCookieStore::new)
             .map(Arc::new)
             .unwrap_or_else(|| Arc::new(AtomicCookieStore::default()));
-        let client = self
-            .client
-            .unwrap_or_else(|| client(cookies.clone(), self.tls));
+        let transport = self
+            .transport
+            .unwrap_or_else(|| Arc::new(ReqwestTransport::new(self.tls)));
+        let transport: Arc<dyn Transport> = match self.record {
+            Some(dir) => Arc::new(RecordTransport::new(transport, dir)),
+            None => transport,
+        };
         let cred = self
             .cred
             .map(AtomicCell::new)
             .unwrap_or_else(|| AtomicCell::new(CredentialStore::default()));
 
+        let mut endpoints = self.endpoints.unwrap_or_default();
+        if self.webvpn {
+            let portal = self.webvpn_portal.as_deref();
+            endpoints = endpoints.webvpn(portal.unwrap_or(Endpoints::WEBVPN_PORTAL));
+        }
+
         Context {
-            client,
+            transport,
             cookies,
             cred,
+            endpoints,
+            retry: self.retry,
+            flights: LoginFlights::default(),
+            layers: self.layers,
+            storage: self.storage,
+            autosave: self.autosave,
+            challenge: self.challenge,
+            auto_continue: self.auto_continue,
+            boya_rsa_key: self.boya_rsa_key,
             _marker: PhantomData,
         }
     }
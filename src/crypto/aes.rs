@@ -351,4 +351,40 @@ impl Aes128 {
 
         output
     }
+
+    /// AES encrypt, use CFB mode with 128-bit segment, NoPadding
+    ///
+    /// The output has the same length as the input
+    pub fn encrypt_cfb(&self, data: &[u8], iv: &[u8; 16]) -> Vec<u8> {
+        let mut output = Vec::with_capacity(data.len());
+        let mut prev_block = *iv;
+
+        for chunk in data.chunks(16) {
+            // 加密上一个密文块作为密钥流
+            let stream = self.encrypt_block(&prev_block);
+            for (i, &c) in chunk.iter().enumerate() {
+                prev_block[i] = c ^ stream[i];
+            }
+            // 最后一块不足 16 字节时截断, 之后也不会再用到 prev_block
+            output.extend_from_slice(&prev_block[..chunk.len()]);
+        }
+
+        output
+    }
+
+    /// AES decrypt, use CFB mode with 128-bit segment, NoPadding
+    pub fn decrypt_cfb(&self, data: &[u8], iv: &[u8; 16]) -> Vec<u8> {
+        let mut output = Vec::with_capacity(data.len());
+        let mut prev_block = *iv;
+
+        for chunk in data.chunks(16) {
+            let stream = self.encrypt_block(&prev_block);
+            for (i, &c) in chunk.iter().enumerate() {
+                output.push(c ^ stream[i]);
+                prev_block[i] = c;
+            }
+        }
+
+        output
+    }
 }
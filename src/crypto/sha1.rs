@@ -2,6 +2,7 @@
 //! and the performance gap is negligible for the small amount of data we pass on
 
 /// Sha1 instance
+#[derive(Clone)]
 pub struct Sha1 {
     state: [u32; 5],
     count: [u64; 2],
@@ -125,3 +126,67 @@ impl Sha1 {
         hasher.finalize()
     }
 }
+
+/// HmacSha1 instance
+pub struct HmacSha1 {
+    // 预先吸收 ipad 和 opad 的哈希状态, PBKDF2 中会被反复使用
+    inner: Sha1,
+    outer: Sha1,
+}
+
+impl HmacSha1 {
+    /// Create a HmacSha1 instance
+    pub fn new(key: &[u8]) -> Self {
+        let mut key_block = [0u8; 64];
+
+        // 如果密钥比块大小长, 先哈希它, 然后补零到块大小
+        if key.len() > 64 {
+            let hash = Sha1::digest(key);
+            key_block[..20].copy_from_slice(&hash);
+        } else {
+            key_block[..key.len()].copy_from_slice(key);
+        }
+
+        let mut k_ipad = [0x36u8; 64];
+        let mut k_opad = [0x5cu8; 64];
+        for ((i, o), k) in k_ipad.iter_mut().zip(k_opad.iter_mut()).zip(key_block) {
+            *i ^= k;
+            *o ^= k;
+        }
+
+        let mut inner = Sha1::new();
+        inner.update(&k_ipad);
+        let mut outer = Sha1::new();
+        outer.update(&k_opad);
+        HmacSha1 { inner, outer }
+    }
+
+    /// Compute the HMAC-SHA1 of the input data
+    pub fn compute(&self, message: &[u8]) -> [u8; 20] {
+        let mut inner = self.inner.clone();
+        inner.update(message);
+        let inner_hash = inner.finalize();
+
+        let mut outer = self.outer.clone();
+        outer.update(&inner_hash);
+        outer.finalize()
+    }
+}
+
+/// PBKDF2 with HMAC-SHA1, fill `output` with the derived key
+pub fn pbkdf2_hmac_sha1(password: &[u8], salt: &[u8], iterations: u32, output: &mut [u8]) {
+    let hmac = HmacSha1::new(password);
+    for (i, chunk) in output.chunks_mut(20).enumerate() {
+        // U1 = PRF(P, S || INT(i)), 块序号从 1 开始
+        let mut msg = Vec::with_capacity(salt.len() + 4);
+        msg.extend_from_slice(salt);
+        msg.extend_from_slice(&(i as u32 + 1).to_be_bytes());
+        let mut u = hmac.compute(&msg);
+        let mut t = u;
+        for _ in 1..iterations {
+            u = hmac.compute(&u);
+            t.iter_mut().zip(u).for_each(|(t, u)| *t ^= u);
+        }
+        chunk.copy_from_slice(&t[..chunk.len()]);
+    }
+}
@@ -22,6 +22,15 @@ mod tests {
         assert_eq!("Qb5wy8PdDSUs6EgTzMX6Gw==", base64);
     }
 
+    #[test]
+    fn test_aes_encrypt_cfb() {
+        let cipher = aes::Aes128::new(b"wrdvpnisthebest!");
+        let encrypted = cipher.encrypt_cfb(b"sso.buaa.edu.cn", b"wrdvpnisthebest!");
+        assert_eq!("e3e44ed225256951300d8db9d6562d", bytes2hex(&encrypted));
+        let encrypted = cipher.encrypt_cfb(b"bykc.buaa.edu.cn", b"wrdvpnisthebest!");
+        assert_eq!("f2ee4a9f69327d517f468ca88d1b203b", bytes2hex(&encrypted));
+    }
+
     #[test]
     fn test_crc() {
         let data = std::fs::read("License").expect("Read License");
@@ -54,6 +63,27 @@ mod tests {
         assert_eq!(&hex, "219e14bef981f117479a7695dacb10c7");
     }
 
+    #[test]
+    fn test_sha1_hmac() {
+        let cipher = sha1::HmacSha1::new(&[0x0b; 20]);
+        let hmac = cipher.compute(b"Hi There");
+        let hex = bytes2hex(&hmac);
+        assert_eq!(&hex, "b617318655057264e28bc0b6fb378c8ef146be00");
+    }
+
+    #[test]
+    fn test_pbkdf2() {
+        let mut key = [0u8; 25];
+        sha1::pbkdf2_hmac_sha1(
+            b"passwordPASSWORDpassword",
+            b"saltSALTsaltSALTsaltSALTsaltSALTsalt",
+            4096,
+            &mut key,
+        );
+        let hex = bytes2hex(&key);
+        assert_eq!(&hex, "3d2eec4fe41c849b80c8d83662c0e44a8b291a964cf2f07038");
+    }
+
     #[test]
     fn test_sha1() {
         let sha1 = sha1::Sha1::digest(b"HelloWorld");
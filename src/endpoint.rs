@@ -0,0 +1,185 @@
+//! Base URL registry of BUAA services
+
+use crate::crypto::aes::Aes128;
+use crate::crypto::bytes2hex;
+
+/// Base URLs of every BUAA host used by the API groups
+///
+/// Each field is a base URL without trailing slash, API paths are appended to it directly.
+/// The default value targets the live campus services.
+///
+/// For integration tests, use [`Endpoints::local`] to point a whole `Context` to a local mock server:
+///
+/// ```
+/// use buaa_api::{ContextBuilder, Endpoints};
+///
+/// let context = ContextBuilder::new()
+///     .endpoints(Endpoints::local("http://127.0.0.1:8080"))
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Endpoints {
+    /// SSO, `https://sso.buaa.edu.cn`
+    pub sso: String,
+    /// Academic Affairs System, `https://byxt.buaa.edu.cn`
+    pub byxt: String,
+    /// Boya Course, `https://bykc.buaa.edu.cn`
+    pub bykc: String,
+    /// Smart Classroom (Web), `https://iclass.buaa.edu.cn:8346`
+    pub iclass: String,
+    /// Smart Classroom (App), `https://iclass.buaa.edu.cn:8347`
+    pub iclass_app: String,
+    /// Smart Classroom (Sign), `http://iclass.buaa.edu.cn:8081`
+    pub iclass_sign: String,
+    /// Cloud Disk, `https://bhpan.buaa.edu.cn`
+    pub bhpan: String,
+    /// Spoc Platform and Teaching Evaluation System, `https://spoc.buaa.edu.cn`
+    pub spoc: String,
+    /// Course Registration System, `https://byxk.buaa.edu.cn`
+    pub byxk: String,
+    /// User Center, `https://uc.buaa.edu.cn`
+    pub uc: String,
+    /// WiFi Gateway, `https://gw.buaa.edu.cn`
+    pub gw: String,
+    /// WiFi Gateway Portal, `http://gw.buaa.edu.cn`
+    pub gw_portal: String,
+    /// App, `https://app.buaa.edu.cn`
+    pub app: String,
+    /// Classroom Live Broadcast API, `https://yjapi.msa.buaa.edu.cn`
+    pub yjapi: String,
+    /// Classroom Live Broadcast Web, `https://classroom.msa.buaa.edu.cn`
+    pub classroom: String,
+    /// WebVPN portal, `https://d.buaa.edu.cn`, `None` for direct access
+    ///
+    /// Set by [`Endpoints::webvpn`], SSO login goes through the portal when it is set
+    pub webvpn: Option<String>,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Endpoints {
+            sso: "https://sso.buaa.edu.cn".to_string(),
+            byxt: "https://byxt.buaa.edu.cn".to_string(),
+            bykc: "https://bykc.buaa.edu.cn".to_string(),
+            iclass: "https://iclass.buaa.edu.cn:8346".to_string(),
+            iclass_app: "https://iclass.buaa.edu.cn:8347".to_string(),
+            iclass_sign: "http://iclass.buaa.edu.cn:8081".to_string(),
+            bhpan: "https://bhpan.buaa.edu.cn".to_string(),
+            spoc: "https://spoc.buaa.edu.cn".to_string(),
+            byxk: "https://byxk.buaa.edu.cn".to_string(),
+            uc: "https://uc.buaa.edu.cn".to_string(),
+            gw: "https://gw.buaa.edu.cn".to_string(),
+            gw_portal: "http://gw.buaa.edu.cn".to_string(),
+            app: "https://app.buaa.edu.cn".to_string(),
+            yjapi: "https://yjapi.msa.buaa.edu.cn".to_string(),
+            classroom: "https://classroom.msa.buaa.edu.cn".to_string(),
+            webvpn: None,
+        }
+    }
+}
+
+impl Endpoints {
+    /// The official WebVPN portal, used by [`crate::ContextBuilder::webvpn`] by default
+    pub const WEBVPN_PORTAL: &str = "https://d.buaa.edu.cn";
+
+    /// Mount every host under a path prefix of one base URL, e.g. `http://127.0.0.1:8080/bykc`
+    ///
+    /// The prefix is the field name, so a single mock server can tell the hosts apart.
+    pub fn local(base: &str) -> Self {
+        let base = base.trim_end_matches('/');
+        let mount = |name: &str| format!("{base}/{name}");
+        Endpoints {
+            sso: mount("sso"),
+            byxt: mount("byxt"),
+            bykc: mount("bykc"),
+            iclass: mount("iclass"),
+            iclass_app: mount("iclass_app"),
+            iclass_sign: mount("iclass_sign"),
+            bhpan: mount("bhpan"),
+            spoc: mount("spoc"),
+            byxk: mount("byxk"),
+            uc: mount("uc"),
+            gw: mount("gw"),
+            gw_portal: mount("gw_portal"),
+            app: mount("app"),
+            yjapi: mount("yjapi"),
+            classroom: mount("classroom"),
+            webvpn: None,
+        }
+    }
+
+    /// Access every host through the WebVPN `portal`, e.g. `https://d.buaa.edu.cn`
+    ///
+    /// All base URLs except the WiFi gateway are rewritten by [`Endpoints::to_webvpn`].
+    /// Calling it twice has no further effect.
+    ///
+    /// ```
+    /// use buaa_api::Endpoints;
+    ///
+    /// let endpoints = Endpoints::default().webvpn(Endpoints::WEBVPN_PORTAL);
+    /// assert_eq!(
+    ///     "https://d.buaa.edu.cn/https/77726476706e69737468656265737421f2ee4a9f69327d517f468ca88d1b203b",
+    ///     endpoints.bykc
+    /// );
+    /// ```
+    pub fn webvpn(mut self, portal: &str) -> Self {
+        if self.webvpn.is_some() {
+            return self;
+        }
+        let portal = portal.trim_end_matches('/');
+        // 校园网网关只能在校内使用, 不经过 WebVPN
+        for base in [
+            &mut self.sso,
+            &mut self.byxt,
+            &mut self.bykc,
+            &mut self.iclass,
+            &mut self.iclass_app,
+            &mut self.iclass_sign,
+            &mut self.bhpan,
+            &mut self.spoc,
+            &mut self.byxk,
+            &mut self.uc,
+            &mut self.app,
+            &mut self.yjapi,
+            &mut self.classroom,
+        ] {
+            *base = Self::to_webvpn(portal, base);
+        }
+        self.webvpn = Some(portal.to_string());
+        self
+    }
+
+    /// Rewrite a URL into WebVPN form, e.g. `https://iclass.buaa.edu.cn:8346/app` to
+    /// `{portal}/https-8346/{encrypted host}/app`
+    ///
+    /// The host is encrypted by AES-128-CFB with a fixed key, and prefixed with the hex of the IV
+    pub fn to_webvpn(portal: &str, url: &str) -> String {
+        const KEY: &[u8; 16] = b"wrdvpnisthebest!";
+
+        let (scheme, rest) = url.split_once("://").unwrap_or(("https", url));
+        let end = rest.find('/').unwrap_or(rest.len());
+        let (authority, path) = rest.split_at(end);
+        // 端口附加在协议后面, 例如 https-8346
+        let (host, scheme) = match authority.split_once(':') {
+            Some((host, port)) => (host, format!("{scheme}-{port}")),
+            None => (authority, scheme.to_string()),
+        };
+        let cipher = Aes128::new(KEY);
+        let encrypted = cipher.encrypt_cfb(host.as_bytes(), KEY);
+        format!(
+            "{}/{scheme}/{}{}{path}",
+            portal.trim_end_matches('/'),
+            bytes2hex(KEY),
+            bytes2hex(&encrypted)
+        )
+    }
+
+    // 取出 base URL 中的 host 部分, WebVPN 下是门户的域名
+    // 各服务共用门户域名, 查找 Cookie 要按 base URL 连同路径匹配, 见 CookieStore::get_by_base
+    /// Get the host part of a base URL, e.g. `bhpan.buaa.edu.cn` for `https://bhpan.buaa.edu.cn`
+    pub fn host(base: &str) -> &str {
+        let rest = base.split_once("://").map(|(_, r)| r).unwrap_or(base);
+        let end = rest.find(['/', ':']).unwrap_or(rest.len());
+        &rest[..end]
+    }
+}
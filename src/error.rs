@@ -54,10 +54,28 @@ impl Error {
         self
     }
 
+    // 复制一份交给其他调用方, 来源只能保留其文本
+    pub(crate) fn share(&self) -> Self {
+        Error {
+            kind: self.kind,
+            message: self.message.clone(),
+            label: self.label,
+            code: self.code,
+            source: self.source.as_ref().map(|s| s.to_string().into()),
+        }
+    }
+
     pub(crate) fn auth(message: impl Into<Cow<'static, str>>) -> Self {
         Self::new(Kind::Auth, message)
     }
 
+    // 服务端拒绝了凭据, 通用请求会据此重新登录一次
+    pub(crate) fn expired(label: &'static str) -> Self {
+        Self::auth("Credential rejected by server")
+            .with_label(label)
+            .with_code(Code::AuthExpired)
+    }
+
     pub(crate) fn io(message: impl Into<Cow<'static, str>>) -> Self {
         Self::new(Kind::Io, message)
     }
@@ -108,6 +126,23 @@ impl Error {
     pub fn code(&self) -> Option<&Code> {
         self.code.as_ref()
     }
+
+    pub(crate) fn is_expired(&self) -> bool {
+        matches!(self.code, Some(Code::AuthExpired))
+    }
+
+    /// Whether simply trying again may succeed, i.e. a transient network failure
+    ///
+    /// DNS failure is excluded, it almost means not connected to BUAA-WiFi.
+    /// Server outcomes like [`Code::BoyaNotOpen`] are not retryable here, check [`Error::code`] for them
+    pub fn is_retryable(&self) -> bool {
+        matches!(self.kind, Kind::Network) && !matches!(self.code, Some(Code::NetworkDnsFailure))
+    }
+
+    /// Whether this is an authentication error, e.g. wrong password or rejected credential
+    pub fn is_auth(&self) -> bool {
+        matches!(self.kind, Kind::Auth)
+    }
 }
 
 impl Display for Error {
@@ -133,7 +168,7 @@ impl Display for Error {
 }
 
 /// The kind of error
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum Kind {
     /// Authentication error
     Auth,
@@ -163,7 +198,8 @@ impl Display for Kind {
 }
 
 /// The specific error code
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum Code {
     // Authentication errors
     /// No Username
@@ -173,6 +209,36 @@ pub enum Code {
     // 在自动刷新机制的帮助下, 这通常不会发生
     /// No Token
     AuthNoToken,
+    /// Credential was rejected by the server before its local expiration
+    AuthExpired,
+    /// SSO asked for a captcha or SMS code, and it was not answered or answered wrong
+    AuthChallenge,
+    /// SSO warned that the password is expired or weak, see [`crate::ContextBuilder::auto_continue`]
+    AuthWeakPassword,
+    /// SSO warned that the account has security risk, see [`crate::ContextBuilder::auto_continue`]
+    AuthAccountRisk,
+    /// The account is locked by SSO
+    AuthLocked,
+    /// Too many failed login attempts, try again later
+    AuthTooManyAttempts,
+
+    // Server errors
+    /// Boya: the course has no vacancy
+    BoyaCourseFull,
+    /// Boya: selection of the course is not open yet
+    BoyaNotOpen,
+    /// Boya: the course conflicts with a selected course in time
+    BoyaTimeConflict,
+    /// Srs: the course conflicts with a selected course
+    SrsConflict,
+    /// Srs: the selection batch is closed or not started
+    SrsBatchClosed,
+    /// Class: nothing to return, e.g. no course today
+    ClassEmptyData,
+    /// Cloud: a file or directory with the same name already exists
+    CloudNameConflict,
+    /// Tes: the task has already been evaluated
+    TesEvaluated,
 
     // Network errors
     /// No local IP address
@@ -184,6 +250,13 @@ pub enum Code {
 
 impl From<reqwest::Error> for Error {
     fn from(value: reqwest::Error) -> Self {
+        // reqwest 会沿来源链按类型查找它自己的 DnsError, 不依赖错误信息的措辞
+        #[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+        if value.is_dns() {
+            return Error::network("DNS resolution failure")
+                .with_code(Code::NetworkDnsFailure)
+                .with_source(value);
+        }
         Error::network("From reqwest crate").with_source(value)
     }
 }
@@ -0,0 +1,203 @@
+//! Background keep-alive of API group sessions
+
+use futures::channel::oneshot;
+use futures::future::{self, Either};
+
+use std::pin::pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::Context;
+use crate::error::Error;
+use crate::request::{group_name, sleep};
+use crate::store::cred::Token;
+
+// 两次检查的最短间隔, 防止 margin 大于 token 时效时空转
+const MIN_INTERVAL: Duration = Duration::from_secs(10);
+// 失败后的重试间隔
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+// 单次睡眠上限, 期间凭据可能被外部替换
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+type ErrorCallback = Arc<dyn Fn(&str, &Error) + Send + Sync>;
+
+/// Options of [`Context::spawn_keepalive`]
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use buaa_api::api::{Boya, Sso};
+/// use buaa_api::{Context, KeepAlive};
+///
+/// # async fn run() -> buaa_api::Result<()> {
+/// let context = Arc::new(Context::new());
+/// context.set_account("username", "password");
+///
+/// let keepalive = KeepAlive::new(Duration::from_secs(60))
+///     .group::<Boya>()
+///     .group::<Sso>()
+///     .on_error(|group, e| eprintln!("{group}: {e}"));
+/// let handle = context.spawn_keepalive(keepalive, |task| {
+///     tokio::spawn(task);
+/// });
+///
+/// // grab courses without waiting for login
+///
+/// handle.stop();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct KeepAlive {
+    groups: Vec<&'static str>,
+    margin: Duration,
+    on_error: Option<ErrorCallback>,
+}
+
+impl std::fmt::Debug for KeepAlive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeepAlive")
+            .field("groups", &self.groups)
+            .field("margin", &self.margin)
+            .finish()
+    }
+}
+
+impl KeepAlive {
+    /// Refresh each added group when less than `margin` of its local lifetime is left
+    ///
+    /// `margin` should be shorter than the token lifetime, otherwise it is refreshed every few seconds
+    pub fn new(margin: Duration) -> Self {
+        KeepAlive {
+            groups: Vec::new(),
+            margin,
+            on_error: None,
+        }
+    }
+
+    /// Keep the session of API group `G` alive, e.g. [`crate::api::Boya`]
+    pub fn group<G: KeepAliveGroup>(mut self) -> Self {
+        let name = group_name::<G>();
+        if !self.groups.contains(&name) {
+            self.groups.push(name);
+        }
+        self
+    }
+
+    /// Call `f` with the group name when a refresh fails, it is retried later
+    ///
+    /// Failures are also reported to [`crate::request::Layer::on_error`]
+    pub fn on_error<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str, &Error) + Send + Sync + 'static,
+    {
+        self.on_error = Some(Arc::new(f));
+        self
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// API groups with a session that [`KeepAlive`] can refresh, e.g. [`crate::api::Boya`]
+pub trait KeepAliveGroup: sealed::Sealed {}
+
+impl<T: Token> sealed::Sealed for T {}
+impl<T: Token> KeepAliveGroup for T {}
+
+/// Handle of a keep-alive task, dropping it also stops the task
+#[derive(Debug)]
+pub struct KeepAliveHandle {
+    stop: oneshot::Sender<()>,
+    running: Arc<AtomicBool>,
+}
+
+impl KeepAliveHandle {
+    /// Stop the task, a refresh in progress is finished first
+    pub fn stop(self) {
+        let _ = self.stop.send(());
+    }
+
+    /// Whether the task is still running
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Acquire)
+    }
+}
+
+pub(crate) fn handle() -> (KeepAliveHandle, oneshot::Receiver<()>, Arc<AtomicBool>) {
+    let (tx, rx) = oneshot::channel();
+    let running = Arc::new(AtomicBool::new(true));
+    let handle = KeepAliveHandle {
+        stop: tx,
+        running: running.clone(),
+    };
+    (handle, rx, running)
+}
+
+pub(crate) async fn run(
+    context: Arc<Context>,
+    keepalive: KeepAlive,
+    mut stop: oneshot::Receiver<()>,
+    running: Arc<AtomicBool>,
+) {
+    loop {
+        let mut failed = false;
+        for &group in &keepalive.groups {
+            if !is_due(&context, group, keepalive.margin) {
+                continue;
+            }
+            if let Err(e) = touch(&context, group).await {
+                log::warn!("Keep-alive of {group} failed: {e}");
+                if let Some(f) = &keepalive.on_error {
+                    f(group, &e);
+                }
+                failed = true;
+            }
+        }
+
+        let report = context.session_report();
+        let next = keepalive
+            .groups
+            .iter()
+            .filter_map(|g| report.get(g))
+            .map(|s| s.remaining.saturating_sub(keepalive.margin))
+            .min()
+            .unwrap_or(MAX_SLEEP);
+        let floor = if failed { RETRY_INTERVAL } else { MIN_INTERVAL };
+        let delay = next.max(floor).min(MAX_SLEEP);
+        // 发送端被丢弃或者主动停止都会结束等待
+        if let Either::Right(_) = future::select(pin!(sleep(delay)), &mut stop).await {
+            break;
+        }
+    }
+    running.store(false, Ordering::Release);
+}
+
+fn is_due(context: &Context, group: &str, margin: Duration) -> bool {
+    context
+        .session_report()
+        .get(group)
+        .is_some_and(|s| s.remaining <= margin)
+}
+
+// 可刷新时效的组访问一个便宜的接口即可续期, 其他组只能重新登录
+// Srs 在选课前登录会导致校验失败, 只访问带 token 的只读接口, 本地过期时由它按需登录
+async fn touch(context: &Context, group: &str) -> crate::Result<()> {
+    match group {
+        "Aas" => context.aas().login().await,
+        "App" => context.app().login().await,
+        "Boya" => context.boya().get_semester().await.map(drop),
+        "Class" => context.class().login().await,
+        "Cloud" => context.cloud().get_user_dir().await.map(drop),
+        "Live" => context.live().login().await,
+        "Spoc" => context.spoc().login().await,
+        "Srs" => context.srs().get_config().await.map(drop),
+        // SSO 登录在 TGC 有效时不会刷新时效, 经由用户中心跳转一次才会
+        "Sso" => context.user().login().await,
+        _ => context.tes().login().await,
+    }
+}
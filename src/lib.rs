@@ -15,14 +15,26 @@
 //! - [`WifiApi`](./api/wifi/type.WifiApi.html)
 
 pub mod api;
+#[cfg(feature = "blocking")]
+pub mod blocking;
 mod cell;
 mod context;
 pub mod crypto;
+mod endpoint;
 pub mod error;
-mod request;
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+mod keepalive;
+#[cfg(feature = "mock")]
+pub mod mock;
+mod pool;
+pub mod request;
 pub mod store;
 mod utils;
 
 pub use crate::utils::time;
 pub use context::{Context, ContextBuilder};
+pub use endpoint::Endpoints;
 pub use error::{Error, Result};
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+pub use keepalive::{KeepAlive, KeepAliveGroup, KeepAliveHandle};
+pub use pool::ContextPool;
@@ -0,0 +1,177 @@
+// 极简 HTTP/1.1 解析与响应, 每个连接只处理一个请求
+
+use reqwest::Url;
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+
+pub(super) struct MockRequest {
+    pub method: String,
+    // 完整 URL, 由 Host 头拼接而成, 用于构造 CAS service
+    pub url: Url,
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl MockRequest {
+    pub fn read(stream: &TcpStream) -> io::Result<MockRequest> {
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let mut parts = line.split_whitespace();
+        let method = parts.next().unwrap_or("GET").to_string();
+        let target = parts.next().unwrap_or("/").to_string();
+
+        let mut headers = HashMap::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                break;
+            }
+            let l = line.trim_end();
+            if l.is_empty() {
+                break;
+            }
+            if let Some((k, v)) = l.split_once(':') {
+                headers.insert(k.trim().to_lowercase(), v.trim().to_string());
+            }
+        }
+
+        let len = headers
+            .get("content-length")
+            .and_then(|l| l.parse::<usize>().ok())
+            .unwrap_or(0);
+        let mut body = vec![0; len];
+        reader.read_exact(&mut body)?;
+
+        let host = headers
+            .get("host")
+            .map(String::as_str)
+            .unwrap_or("localhost");
+        let url = Url::parse(&format!("http://{host}{target}"))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(MockRequest {
+            method,
+            url,
+            headers,
+            body,
+        })
+    }
+
+    pub fn query(&self, name: &str) -> Option<String> {
+        self.url
+            .query_pairs()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.into_owned())
+    }
+
+    pub fn form(&self, name: &str) -> Option<String> {
+        serde_urlencoded::from_bytes::<Vec<(String, String)>>(&self.body)
+            .ok()?
+            .into_iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v)
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+
+    pub fn cookie(&self, name: &str) -> Option<&str> {
+        self.header("cookie")?
+            .split("; ")
+            .filter_map(|c| c.split_once('='))
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| v)
+    }
+
+    // 当前服务的根地址, 例如 http://127.0.0.1:8080
+    pub fn origin(&self) -> String {
+        self.url.origin().ascii_serialization()
+    }
+}
+
+pub(super) struct MockResponse {
+    status: u16,
+    headers: Vec<(&'static str, String)>,
+    body: Vec<u8>,
+}
+
+impl MockResponse {
+    pub fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        MockResponse {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+        Self::new(200, body)
+    }
+
+    pub fn json(body: impl Into<Vec<u8>>) -> Self {
+        Self::ok(body).header("Content-Type", "application/json")
+    }
+
+    pub fn redirect(location: impl Into<String>) -> Self {
+        Self::new(302, "").header("Location", location)
+    }
+
+    pub fn not_found() -> Self {
+        Self::new(404, "Not Found")
+    }
+
+    pub fn header(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.headers.push((name, value.into()));
+        self
+    }
+
+    pub fn map_headers(mut self, f: impl Fn(&str, String) -> String) -> Self {
+        self.headers = self
+            .headers
+            .into_iter()
+            .map(|(k, v)| {
+                let v = f(k, v);
+                (k, v)
+            })
+            .collect();
+        self
+    }
+
+    pub fn cookie(self, name: &str, value: &str, path: &str) -> Self {
+        self.header("Set-Cookie", format!("{name}={value}; Path={path}"))
+    }
+
+    pub fn write(self, mut stream: &TcpStream) -> io::Result<()> {
+        let mut head = format!(
+            "HTTP/1.1 {} {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+            self.status,
+            reason(self.status),
+            self.body.len()
+        );
+        for (k, v) in self.headers {
+            head.push_str(&format!("{k}: {v}\r\n"));
+        }
+        head.push_str("\r\n");
+        stream.write_all(head.as_bytes())?;
+        stream.write_all(&self.body)?;
+        stream.flush()
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        302 => "Found",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
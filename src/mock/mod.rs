@@ -0,0 +1,262 @@
+//! Mock BUAA server for end-to-end tests
+//!
+//! Every host is mounted under the path prefix of [`Endpoints::local`],
+//! so one [`MockServer`] serves a whole [`crate::Context`]:
+//!
+//! ```no_run
+//! use buaa_api::mock::MockServer;
+//!
+//! # async fn run() -> buaa_api::Result<()> {
+//! let server = MockServer::start("127.0.0.1:0").unwrap();
+//! let context = server.builder().build();
+//! context.set_account(MockServer::USERNAME, MockServer::PASSWORD);
+//! context.login().await?;
+//! // Invalidate every session to test re-login and error paths
+//! server.expire_sessions();
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! It emulates the SSO CAS flow, the login handshake of every API group,
+//! and returns representative JSON for some group APIs. It is NOT a full copy of the servers.
+
+mod http;
+mod route;
+mod test;
+
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, mpsc};
+use std::thread::{self, JoinHandle};
+
+use crate::{ContextBuilder, Endpoints};
+
+use http::{MockRequest, MockResponse};
+
+/// Public key paired with the private key of the mock Boya server
+pub const MOCK_BOYA_RSA_KEY: &str = "-----BEGIN PUBLIC KEY-----
+MIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQKBgQDVwGybjlt6OGfEWw6tW7S1SXXI
+mjQ1fW6S0px1L0dJ6Y2/ZwuYC7gjcbBZCtAOOjVjYSlDPZhM1h4qo3TH5TCxwgcX
+aiVn7cAwONfogfO6wYQQODUKL23EwDHSqaR4QxOVLkPEx8C26fUwHsUzEGowMDgH
+hVZiw2CpvgRXaDx3BQIDAQAB
+-----END PUBLIC KEY-----";
+
+// 处理连接的线程数, 响应都是 Connection: close, 足够测试中的并发
+const WORKERS: usize = 8;
+
+// 服务端状态, 所有会话都只是一个随机字符串
+#[derive(Default)]
+pub(crate) struct State {
+    username: String,
+    password: String,
+    risk: bool,
+    weak: bool,
+    locked: bool,
+    wrong_attempts: usize,
+    captcha: bool,
+    sms: bool,
+    course_full: bool,
+    counter: u64,
+    // 已签发且有效的 TGC, ST, 各组 token 等
+    sessions: HashSet<String>,
+    // 等待 ignoreAndContinue 的 execution
+    executions: HashSet<String>,
+    // WiFi 用户名对应的 challenge
+    challenges: HashMap<String, String>,
+    // 一次性故障注入, 路径前缀与状态码
+    failures: Vec<(String, u16)>,
+    // 成功的 SSO 登录次数
+    logins: usize,
+}
+
+impl State {
+    // 生成不登记的一次性标识, 例如 execution 和 login_challenge
+    fn id(&mut self, prefix: &str) -> String {
+        self.counter += 1;
+        format!("{prefix}-{:08x}", self.counter.wrapping_mul(0x9E37_79B9))
+    }
+
+    // 签发并登记一个有效会话
+    fn issue(&mut self, prefix: &str) -> String {
+        let token = self.id(prefix);
+        self.sessions.insert(token.clone());
+        token
+    }
+
+    fn valid(&self, token: Option<&str>) -> bool {
+        token.is_some_and(|t| self.sessions.contains(t))
+    }
+
+    // ST 只能使用一次
+    fn consume(&mut self, ticket: Option<&str>) -> bool {
+        ticket.is_some_and(|t| t.starts_with("ST-") && self.sessions.remove(t))
+    }
+}
+
+/// Mock BUAA server running on a background thread
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    shutdown: Arc<AtomicBool>,
+    listener: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// Default accepted username
+    pub const USERNAME: &str = "mock";
+    /// Default accepted password
+    pub const PASSWORD: &str = "mock";
+    /// Answer of every captcha
+    pub const CAPTCHA: &str = "8888";
+    /// Every SMS verification code
+    pub const SMS_CODE: &str = "123456";
+
+    /// Bind to `addr` and serve on a background thread. Use port `0` to pick a free port
+    pub fn start(addr: &str) -> io::Result<MockServer> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State {
+            username: Self::USERNAME.to_string(),
+            password: Self::PASSWORD.to_string(),
+            ..Default::default()
+        }));
+
+        // 固定数量的工作线程, 监听线程退出后通道关闭, 它们也随之退出
+        let (tx, rx) = mpsc::channel::<TcpStream>();
+        let rx = Arc::new(Mutex::new(rx));
+        for _ in 0..WORKERS {
+            let rx = rx.clone();
+            let state = state.clone();
+            thread::spawn(move || {
+                loop {
+                    let stream = match rx.lock().unwrap_or_else(|e| e.into_inner()).recv() {
+                        Ok(stream) => stream,
+                        Err(_) => break,
+                    };
+                    let res = MockRequest::read(&stream)
+                        .map(|req| route::handle(&state, req))
+                        .unwrap_or_else(|e| MockResponse::new(400, e.to_string()));
+                    if let Err(e) = res.write(&stream) {
+                        log::debug!("Mock write failed: {e}");
+                    }
+                }
+            });
+        }
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let stop = shutdown.clone();
+        let listener = thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if stop.load(Ordering::Acquire) || tx.send(stream).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(MockServer {
+            addr,
+            state,
+            shutdown,
+            listener: Some(listener),
+        })
+    }
+
+    /// Get the bound address
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Get the base URL, e.g. `http://127.0.0.1:8080`
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Get [`Endpoints`] pointing to this server
+    pub fn endpoints(&self) -> Endpoints {
+        Endpoints::local(&self.base_url())
+    }
+
+    /// Get a [`ContextBuilder`] with [`MockServer::endpoints`] and [`MOCK_BOYA_RSA_KEY`]
+    pub fn builder(&self) -> ContextBuilder {
+        ContextBuilder::new()
+            .endpoints(self.endpoints())
+            .boya_rsa_key(MOCK_BOYA_RSA_KEY)
+    }
+
+    /// Set the accepted account of SSO and WiFi
+    pub fn set_account(&self, username: &str, password: &str) {
+        let mut state = self.state();
+        state.username = username.to_string();
+        state.password = password.to_string();
+        state.wrong_attempts = 0;
+    }
+
+    /// Whether SSO login shows the "account has security risk" page with `continueForm`
+    pub fn set_risk(&self, risk: bool) {
+        self.state().risk = risk;
+    }
+
+    /// Whether SSO login shows the "password expired or weak" page with `continueForm`
+    pub fn set_weak_password(&self, weak: bool) {
+        self.state().weak = weak;
+    }
+
+    /// Whether the account is locked, SSO refuses every password login
+    ///
+    /// After 5 wrong passwords in a row, SSO also refuses as too many attempts,
+    /// until [`MockServer::set_account`] is called
+    pub fn set_locked(&self, locked: bool) {
+        self.state().locked = locked;
+    }
+
+    /// Whether SSO login page requires a captcha, the answer is [`MockServer::CAPTCHA`]
+    pub fn set_captcha(&self, captcha: bool) {
+        self.state().captcha = captcha;
+    }
+
+    /// Whether SSO login requires an SMS code after the password, the code is [`MockServer::SMS_CODE`]
+    pub fn set_sms(&self, sms: bool) {
+        self.state().sms = sms;
+    }
+
+    /// Whether Boya refuses every course selection as full
+    pub fn set_course_full(&self, full: bool) {
+        self.state().course_full = full;
+    }
+
+    /// Invalidate every issued session and token, as if they all expired on the server
+    pub fn expire_sessions(&self) {
+        self.state().sessions.clear();
+    }
+
+    /// Make the next request whose path starts with `path` fail with `status`
+    ///
+    /// The path includes the mount prefix, e.g. `/bykc/sscv/getAllConfig`
+    pub fn fail_next(&self, path: &str, status: u16) {
+        self.state().failures.push((path.to_string(), status));
+    }
+
+    /// Get the number of successful SSO logins
+    pub fn sso_logins(&self) -> usize {
+        self.state().logins
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for MockServer {
+    // 监听线程阻塞在 accept 上, 连一次自己把它唤醒
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Release);
+        // 连不上时不等待, 以免卡住
+        if TcpStream::connect(self.addr).is_ok()
+            && let Some(listener) = self.listener.take()
+        {
+            let _ = listener.join();
+        }
+    }
+}
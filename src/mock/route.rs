@@ -0,0 +1,796 @@
+// 按挂载前缀分发请求, 每个函数模拟一个域名
+// 登录流程尽量与真实服务一致, 业务接口只返回有代表性的固定 JSON
+
+use base64::engine::{Engine, general_purpose};
+
+use std::sync::Mutex;
+
+use crate::Endpoints;
+use crate::crypto::{self, aes::Aes128, biguint::BigUint, des::Des, md5::HmacMd5, sha1::Sha1};
+use crate::utils;
+
+use super::http::{MockRequest, MockResponse};
+use super::{MockServer, State};
+
+// 与 MOCK_BOYA_RSA_KEY 配对的模数和私钥指数
+const MOCK_BOYA_RSA_N: &str = "d5c06c9b8e5b7a3867c45b0ead5bb4b54975c89a34357d6e92d29c752f4749e98dbf670b980bb82371b0590ad00e3a35636129433d984cd61e2aa374c7e530b1c207176a2567edc03038d7e881f3bac1841038350a2f6dc4c031d2a9a4784313952e43c4c7c0b6e9f5301ec533106a30303807855662c360a9be0457683c7705";
+const MOCK_BOYA_RSA_D: &str = "1aa594648808ab61e0bdbfc442a44e10d389956a73dbbf394ba069d4ca020f1ac88af5e3a21ec3e3d7466eb34460a1ad0a2890a64b45733eb62006a4864ef214dc5613e8af615aac12bfe968619564cc9de5343871b99c0be053192dc542861afdf816823efccd5b746de08bb77dbf45347bfa40003852eb12e9c415bb634c81";
+
+// 连续输错密码的次数上限, 之后拒绝一切密码登录
+const MAX_WRONG_ATTEMPTS: usize = 5;
+
+// 与 Endpoints::to_webvpn 相同的密钥
+const WEBVPN_KEY: &[u8; 16] = b"wrdvpnisthebest!";
+
+// 与 ClassApi 相同的 DES 密钥
+const CLASS_DES_KEY: &[u8] = b"Jyd#351*";
+
+pub(super) fn handle(state: &Mutex<State>, req: MockRequest) -> MockResponse {
+    // WebVPN 门户先解出真实地址再转发, 不占用下面的锁
+    if req.url.path().starts_with("/webvpn/") {
+        return webvpn(state, req);
+    }
+    let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+    let path = req.url.path().to_string();
+    log::debug!("Mock {} {}", req.method, req.url);
+
+    if let Some(i) = state
+        .failures
+        .iter()
+        .position(|(p, _)| path.starts_with(p.as_str()))
+    {
+        let (_, status) = state.failures.remove(i);
+        return MockResponse::new(status, "Mock failure");
+    }
+
+    let path = path.trim_start_matches('/');
+    let (group, rest) = path.split_once('/').unwrap_or((path, ""));
+    let state = &mut *state;
+    match group {
+        "sso" => sso(state, &req, rest),
+        "bykc" => bykc(state, &req, rest),
+        "iclass" => iclass(state, &req, rest),
+        "iclass_app" | "iclass_sign" => iclass_app(state, &req, rest),
+        "gw_portal" => gw_portal(),
+        "gw" => gw(state, &req, rest),
+        "spoc" => spoc(state, &req, rest),
+        "byxk" => byxk(state, &req, rest),
+        "byxt" => byxt(state, &req, rest),
+        "app" => app(state, &req, rest),
+        "uc" => uc(state, &req, rest),
+        "yjapi" => yjapi(state, &req, rest),
+        "classroom" => MockResponse::ok("<html>Classroom</html>"),
+        "bhpan" => bhpan(state, &req, rest),
+        _ => MockResponse::not_found(),
+    }
+}
+
+// 没有有效会话时转跳 SSO, 登录后 SSO 会带着 ST 回到 service
+fn cas(req: &MockRequest, service: &str) -> MockResponse {
+    MockResponse::redirect(format!(
+        "{}/sso/login?service={}",
+        req.origin(),
+        utils::url_encode(service)
+    ))
+}
+
+fn bearer(req: &MockRequest) -> Option<&str> {
+    req.header("authorization")?.strip_prefix("Bearer ")
+}
+
+fn hex_decode(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .filter_map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
+}
+
+// ====================
+// WebVPN
+// ====================
+
+// 门户自身只处理 CAS 登录, 其余路径解出真实地址后转发, 再把响应改写回门户
+// 简化: 转发不检查门户票据, 真实门户会要求先登录
+fn webvpn(state: &Mutex<State>, req: MockRequest) -> MockResponse {
+    let origin = req.origin();
+    let portal = format!("{origin}/webvpn");
+    let rest = req.url.path()["/webvpn/".len()..].to_string();
+    match rest.as_str() {
+        "" => return MockResponse::ok("<html>WebVPN</html>"),
+        "login" => {
+            let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+            return if state.consume(req.query("ticket").as_deref()) {
+                let ticket = state.issue("VPN");
+                MockResponse::redirect(format!("{portal}/")).cookie(
+                    "wengine_vpn_ticket",
+                    &ticket,
+                    "/webvpn",
+                )
+            } else {
+                MockResponse::new(403, "Forbidden")
+            };
+        }
+        _ => {}
+    }
+
+    // {scheme}[-{port}]/{IV 的 hex}{加密 host 的 hex}{path}
+    let Some((segment, rest)) = rest.split_once('/') else {
+        return MockResponse::not_found();
+    };
+    let (hex, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let Ok(iv) = <[u8; 16]>::try_from(hex_decode(hex.get(..32).unwrap_or_default())) else {
+        return MockResponse::not_found();
+    };
+    let host = Aes128::new(WEBVPN_KEY).decrypt_cfb(&hex_decode(&hex[32..]), &iv);
+    let host = String::from_utf8_lossy(&host);
+    let (scheme, port) = match segment.split_once('-') {
+        Some((scheme, port)) => (scheme, format!(":{port}")),
+        None => (segment, String::new()),
+    };
+    let path = if path.is_empty() { "/" } else { path };
+    let query = req.url.query().map(|q| format!("?{q}")).unwrap_or_default();
+    let Ok(url) = reqwest::Url::parse(&format!("{scheme}://{host}{port}{path}{query}")) else {
+        return MockResponse::not_found();
+    };
+
+    let prefix = format!("/webvpn/{segment}/{hex}");
+    let inner = MockRequest {
+        method: req.method,
+        url,
+        headers: req.headers,
+        body: req.body,
+    };
+    handle(state, inner).map_headers(|name, value| match name {
+        "Location" if value.starts_with(&origin) && !value.starts_with(&portal) => {
+            Endpoints::to_webvpn(&portal, &value)
+        }
+        "Set-Cookie" => scope_cookie(&value, &prefix),
+        _ => value,
+    })
+}
+
+// 上游 Cookie 都落在门户域名下, 去掉 Domain, Path 加上服务的前缀
+fn scope_cookie(raw: &str, prefix: &str) -> String {
+    let mut path = "/";
+    let mut parts = Vec::new();
+    for (i, part) in raw.split("; ").enumerate() {
+        match part.split_once('=') {
+            Some((k, v)) if i > 0 && k.eq_ignore_ascii_case("path") => path = v,
+            Some((k, _)) if i > 0 && k.eq_ignore_ascii_case("domain") => {}
+            _ => parts.push(part),
+        }
+    }
+    format!("{}; Path={prefix}{path}", parts.join("; "))
+}
+
+// ====================
+// SSO
+// ====================
+
+fn login_page(state: &mut State) -> String {
+    let execution = state.id("e1s1");
+    let captcha = if state.captcha {
+        "<input type=\"text\" name=\"captcha\"/><img id=\"captchaImg\" src=\"captcha?r=1\"/>"
+    } else {
+        ""
+    };
+    format!(
+        "<html><form id=\"fm1\" method=\"post\">\
+         <input type=\"hidden\" name=\"execution\" value=\"{execution}\"/>{captcha}\
+         </form></html>"
+    )
+}
+
+// 短信已发送的二次验证页, execution 登记后只能使用一次
+fn sms_page(state: &mut State) -> String {
+    let e = state.id("e2s2");
+    state.executions.insert(e.clone());
+    format!(
+        "<html><p>验证码已发送至 138****0000</p><form id=\"fm1\" method=\"post\">\
+         <input type=\"hidden\" name=\"execution\" value=\"{e}\"/>\
+         <input type=\"text\" name=\"token\"/>\
+         </form></html>"
+    )
+}
+
+fn sso(state: &mut State, req: &MockRequest, rest: &str) -> MockResponse {
+    if rest == "logout" {
+        if let Some(tgc) = req.cookie("CASTGC") {
+            state.sessions.remove(tgc);
+        }
+        return MockResponse::ok("<html>Logout Success</html>")
+            .header("Set-Cookie", "CASTGC=; Max-Age=0; Path=/sso");
+    }
+    if rest == "captcha" {
+        return MockResponse::ok(b"\x89PNG mock captcha".to_vec())
+            .header("Content-Type", "image/png");
+    }
+    if rest != "login" {
+        return MockResponse::not_found();
+    }
+    if req.method == "GET" {
+        if state.valid(req.cookie("CASTGC")) {
+            return match req.query("service") {
+                Some(service) => {
+                    let st = state.issue("ST");
+                    let sep = if service.contains('?') { '&' } else { '?' };
+                    MockResponse::redirect(format!("{service}{sep}ticket={st}"))
+                }
+                None => MockResponse::redirect(format!("{}/uc/", req.origin())),
+            };
+        }
+        return MockResponse::ok(login_page(state));
+    }
+
+    let execution = req.form("execution");
+    let event = req.form("_eventId");
+    let login = |state: &mut State| {
+        state.logins += 1;
+        let tgc = state.issue("TGT");
+        MockResponse::ok("<html>Login Success</html>").cookie("CASTGC", &tgc, "/sso")
+    };
+    match event.as_deref() {
+        Some("ignoreAndContinue") => match execution {
+            Some(e) if state.executions.remove(&e) => login(state),
+            _ => MockResponse::new(401, login_page(state)),
+        },
+        Some("submit") if req.form("token").is_some() => match execution {
+            Some(e)
+                if state.executions.remove(&e)
+                    && req.form("token").as_deref() == Some(MockServer::SMS_CODE) =>
+            {
+                login(state)
+            }
+            _ => MockResponse::new(401, login_page(state)),
+        },
+        Some("submit") if state.locked => {
+            MockResponse::new(401, "<html><p>您的账号已被锁定, 请联系管理员</p></html>")
+        }
+        Some("submit") if state.wrong_attempts >= MAX_WRONG_ATTEMPTS => {
+            MockResponse::new(401, "<html><p>登录失败次数过多, 请稍后再试</p></html>")
+        }
+        Some("submit")
+            if execution.is_some()
+                && req.form("username").as_deref() == Some(&state.username)
+                && req.form("password").as_deref() == Some(&state.password)
+                && (!state.captcha
+                    || req.form("captcha").as_deref() == Some(MockServer::CAPTCHA)) =>
+        {
+            state.wrong_attempts = 0;
+            if state.sms {
+                let page = sms_page(state);
+                MockResponse::new(401, page)
+            } else if state.weak {
+                let page = warning_page(state, "您的密码已过期或是已知的弱密码");
+                MockResponse::new(401, page)
+            } else if state.risk {
+                let page = warning_page(state, "您的账号存在安全风险");
+                MockResponse::new(401, page)
+            } else {
+                login(state)
+            }
+        }
+        Some("submit") => {
+            state.wrong_attempts += 1;
+            let page = login_page(state);
+            // 只有验证码答错时不提示密码错误
+            if req.form("username").as_deref() == Some(&state.username)
+                && req.form("password").as_deref() == Some(&state.password)
+            {
+                MockResponse::new(401, page)
+            } else {
+                let page = page.replace("<form", "<p>用户名或密码错误</p><form");
+                MockResponse::new(401, page)
+            }
+        }
+        _ => MockResponse::new(401, login_page(state)),
+    }
+}
+
+// 密码正确后的警告页, 可以 ignoreAndContinue
+fn warning_page(state: &mut State, text: &str) -> String {
+    let e = state.id("e2s1");
+    state.executions.insert(e.clone());
+    format!(
+        "<html><p>{text}</p><form id=\"continueForm\" method=\"post\">\
+         <input type=\"hidden\" name=\"execution\" value=\"{e}\"/>\
+         </form></html>"
+    )
+}
+
+// ====================
+// Boya
+// ====================
+
+fn bykc(state: &mut State, req: &MockRequest, rest: &str) -> MockResponse {
+    let origin = req.origin();
+    match rest {
+        "sscv/cas/login" => {
+            if state.consume(req.query("ticket").as_deref()) {
+                let token = state.issue("BOYA");
+                MockResponse::redirect(format!("{origin}/bykc/cas-login?token={token}"))
+            } else {
+                cas(req, &format!("{origin}/bykc/sscv/cas/login"))
+            }
+        }
+        "cas-login" => MockResponse::ok("<html>Boya</html>"),
+        _ => match rest.strip_prefix("sscv/") {
+            Some(op) if req.method == "POST" => boya_api(state, req, op),
+            _ => MockResponse::not_found(),
+        },
+    }
+}
+
+// RSA 解密出 AES 密钥, 只需要去掉 PKCS#1 v1.5 填充
+fn rsa_decrypt(data: &[u8]) -> Option<Vec<u8>> {
+    let n = BigUint::from_bytes_be(&hex_decode(MOCK_BOYA_RSA_N));
+    let d = BigUint::from_bytes_be(&hex_decode(MOCK_BOYA_RSA_D));
+    // 前导 0x00 已被去掉, 剩下 0x02 PS 0x00 M
+    let m = BigUint::from_bytes_be(data).modpow(&d, &n).to_bytes_be();
+    let start = m.iter().skip(1).position(|&b| b == 0)? + 2;
+    Some(m[start..].to_vec())
+}
+
+fn boya_api(state: &mut State, req: &MockRequest, op: &str) -> MockResponse {
+    let key = req
+        .header("ak")
+        .and_then(|ak| general_purpose::STANDARD.decode(ak).ok())
+        .and_then(|ak| rsa_decrypt(&ak));
+    let Some(key) = key.filter(|k| k.len() == 16) else {
+        return MockResponse::new(400, "Bad Ak");
+    };
+    let aes = Aes128::new(&key);
+
+    let data = if !state.valid(req.header("authtoken")) {
+        r#"{"status":"1","errmsg":"登录已过期, 请重新登录","data":null}"#
+    } else {
+        match op {
+            "getAllConfig" => {
+                r#"{"status":"0","errmsg":"请求成功","data":{"semester":[{"semesterStartDate":"2025-09-01 00:00:00","semesterEndDate":"2026-01-18 23:59:59"}]}}"#
+            }
+            "choseCourse" if state.course_full => {
+                r#"{"status":"1","errmsg":"该课程人数已满","data":null}"#
+            }
+            "choseCourse" | "delChosenCourse" => {
+                r#"{"status":"0","errmsg":"请求成功","data":{"courseCurrentCount":1}}"#
+            }
+            _ => r#"{"status":"1","errmsg":"接口未模拟","data":null}"#,
+        }
+    };
+    // 与真实服务一致, 响应体是带引号的 Base64 字符串
+    let body = crypto::encode_base64(aes.encrypt_ecb(data.as_bytes()));
+    MockResponse::json(format!("\"{body}\""))
+}
+
+// ====================
+// Class
+// ====================
+
+fn iclass(state: &mut State, req: &MockRequest, rest: &str) -> MockResponse {
+    let origin = req.origin();
+    match rest {
+        "" => cas(req, &format!("{origin}/iclass/cas")),
+        "cas" => {
+            if state.consume(req.query("ticket").as_deref()) {
+                let session = state.issue("CLS");
+                MockResponse::redirect(format!("{origin}/iclass/index.html?loginName={session}"))
+            } else {
+                cas(req, &format!("{origin}/iclass/cas"))
+            }
+        }
+        "index.html" => MockResponse::ok("<html>iClass</html>"),
+        "wc/auth/html5GetPrivateUserInfo" => {
+            // 无法解密, 只能逐个加密有效的 loginName URL 比对
+            let cipher = Des::new(CLASS_DES_KEY);
+            let url = req.query("url").unwrap_or_default();
+            let matched = state
+                .sessions
+                .iter()
+                .filter(|s| s.starts_with("CLS-"))
+                .any(|s| {
+                    let expected = format!("{origin}/iclass/index.html?loginName={s}");
+                    crypto::bytes2hex(&cipher.encrypt_ecb(expected.as_bytes())) == url
+                });
+            if matched {
+                MockResponse::json(r#"{"STATUS":"0","ERRMSG":""}"#)
+            } else {
+                MockResponse::json(r#"{"STATUS":"1","ERRMSG":"url 校验失败"}"#)
+            }
+        }
+        _ => MockResponse::not_found(),
+    }
+}
+
+fn iclass_app(state: &mut State, req: &MockRequest, rest: &str) -> MockResponse {
+    if rest == "app/user/login.action" {
+        let session = req.query("phone");
+        if !state.valid(session.as_deref()) {
+            return MockResponse::json(r#"{"STATUS":"1","ERRMSG":"用户不存在"}"#);
+        }
+        let id = state.issue("UID");
+        return MockResponse::json(format!(
+            r#"{{"STATUS":"0","ERRMSG":"","result":{{"id":"{id}","sessionId":"{}","userName":"mock"}}}}"#,
+            session.unwrap_or_default()
+        ));
+    }
+
+    if !(state.valid(req.header("sessionid")) && state.valid(req.query("id").as_deref())) {
+        return MockResponse::json(r#"{"STATUS":"1","ERRMSG":"请先登录"}"#);
+    }
+    match rest {
+        "app/course/get_stu_course_sched.action" => MockResponse::json(
+            r#"{"STATUS":"0","ERRMSG":"","result":[{"id":"1001","courseId":"C1","courseName":"软件工程","teacherName":"张三","classBeginTime":"2025-09-01 08:00:00","signStatus":"0"}]}"#,
+        ),
+        "app/choosecourse/get_myall_course.action" => MockResponse::json(
+            r#"{"STATUS":"0","ERRMSG":"","result":[{"course_id":"C1","course_name":"软件工程","teacher_name":"张三"},{"course_id":"C2","course_name":"无效课程","teacher_name":""}]}"#,
+        ),
+        "app/my/get_my_course_sign_detail.action" => MockResponse::json(
+            r#"{"STATUS":"0","ERRMSG":"","result":[{"courseSchedId":"1001","classBeginTime":"2025-09-01 08:00:00","signStatus":"0"}]}"#,
+        ),
+        "app/common/get_timestamp.action" => {
+            MockResponse::json(r#"{"STATUS":"0","ERRMSG":"","timestamp":1756684800000}"#)
+        }
+        "app/course/stu_scan_sign.action" => MockResponse::json(
+            r#"{"STATUS":"0","ERRMSG":"","result":{"stuSignId":"1","stuSignStatus":"1"}}"#,
+        ),
+        _ => MockResponse::not_found(),
+    }
+}
+
+// ====================
+// WiFi
+// ====================
+
+fn gw_portal() -> MockResponse {
+    MockResponse::ok(
+        "<html><head><meta http-equiv=\"refresh\" content=\"0;url=/srun_portal_pc?ac_id=1&theme=buaa\"></head></html>",
+    )
+}
+
+fn gw(state: &mut State, req: &MockRequest, rest: &str) -> MockResponse {
+    let callback = req.query("callback").unwrap_or_default();
+    let username = req.query("username").unwrap_or_default();
+    let ip = req.query("ip").unwrap_or_default();
+    let jsonp = |json: &str| MockResponse::ok(format!("{callback}({json})"));
+    match rest {
+        "cgi-bin/get_challenge" => {
+            let challenge = crypto::bytes2hex(&Sha1::digest(state.id("challenge").as_bytes()));
+            state.challenges.insert(username, challenge.clone());
+            jsonp(&format!(
+                r#"{{"challenge":"{challenge}","client_ip":"{ip}","ecode":0,"error":"ok","error_msg":"","expire":"60","online_ip":"{ip}","res":"ok","st":0}}"#
+            ))
+        }
+        "cgi-bin/srun_portal" => match req.query("action").as_deref() {
+            Some("login") => {
+                // 按客户端相同的算法校验密码和校验和
+                let checked = state.challenges.get(&username).is_some_and(|token| {
+                    let pw = HmacMd5::new(token.as_bytes()).compute(state.password.as_bytes());
+                    let pw = crypto::bytes2hex(&pw);
+                    let ac_id = req.query("ac_id").unwrap_or_default();
+                    let info = req.query("info").unwrap_or_default();
+                    let sum = format!(
+                        "{token}{username}{token}{pw}{token}{ac_id}{token}{ip}{token}200{token}1{token}{info}"
+                    );
+                    let sum = crypto::bytes2hex(&Sha1::digest(sum.as_bytes()));
+                    username == state.username
+                        && req.query("password") == Some(format!("{{MD5}}{pw}"))
+                        && req.query("chksum") == Some(sum)
+                });
+                if checked {
+                    jsonp(
+                        r#"{"ServerFlag":0,"error":"ok","ploy_msg":"E0000: Login is successful.","suc_msg":"login_ok"}"#,
+                    )
+                } else {
+                    jsonp(
+                        r#"{"error":"login_error","error_msg":"E2901: (Third party 1)bind_user2: ldap_bind error","res":"login_error"}"#,
+                    )
+                }
+            }
+            Some("logout") if username == state.username => jsonp(r#"{"error":"ok","res":"ok"}"#),
+            _ => jsonp(r#"{"error":"logout_error","res":"logout_error"}"#),
+        },
+        _ => MockResponse::not_found(),
+    }
+}
+
+// ====================
+// Spoc & Tes
+// ====================
+
+fn spoc(state: &mut State, req: &MockRequest, rest: &str) -> MockResponse {
+    let origin = req.origin();
+    match rest {
+        "spocnewht/cas" => {
+            if state.consume(req.query("ticket").as_deref()) {
+                let token = state.issue("SPOC");
+                let refresh = state.id("SPOCR");
+                MockResponse::redirect(format!(
+                    "{origin}/spoc/spocnew/index?token={token}&refreshToken={refresh}"
+                ))
+            } else {
+                cas(req, &format!("{origin}/spoc/spocnewht/cas"))
+            }
+        }
+        "spocnew/index" => MockResponse::ok("<html>Spoc</html>"),
+        "pjxt/cas" => {
+            if state.consume(req.query("ticket").as_deref()) {
+                let session = state.issue("PJXT");
+                MockResponse::redirect(format!("{origin}/spoc/pjxt/")).cookie(
+                    "PJXT_SESSION",
+                    &session,
+                    "/spoc/pjxt",
+                )
+            } else {
+                cas(req, &format!("{origin}/spoc/pjxt/cas"))
+            }
+        }
+        "pjxt/" => MockResponse::ok("<html>Tes</html>"),
+        _ if rest.starts_with("pjxt/") => tes(state, req, &rest[5..]),
+        _ => {
+            let token = req.header("token").and_then(|t| t.strip_prefix("Inco-"));
+            if !state.valid(token) {
+                return MockResponse::json(r#"{"code":401,"msg":"登录已过期","content":null}"#);
+            }
+            match rest {
+                "spocnewht/inco/ht/queryOne" => MockResponse::json(
+                    r#"{"code":200,"msg":"OK","content":{"pjmrrq":"1,2025-09-01 00:00:00,2025-09-07 23:59:59","mrxq":"2025-20261"}}"#,
+                ),
+                _ => MockResponse::not_found(),
+            }
+        }
+    }
+}
+
+// 会话失效时与真实服务一样重新走 CAS, TGC 也失效时停在 SSO 登录页
+fn tes(state: &mut State, req: &MockRequest, api: &str) -> MockResponse {
+    if !state.valid(req.cookie("PJXT_SESSION")) {
+        return cas(req, &format!("{}/spoc/pjxt/cas", req.origin()));
+    }
+    match api {
+        "personnelEvaluation/listObtainPersonnelEvaluationTasks" => MockResponse::json(
+            r#"{"code":"200","msg":"成功","result":{"total":1,"list":[{"rwid":"RW1","rwmc":"2025-2026-1 评教","pjsl":1,"ypjsl":0}]}}"#,
+        ),
+        "evaluationMethodSix/getQuestionnaireListToTask" => MockResponse::json(
+            r#"{"code":"200","msg":"成功","result":[{"wjid":"WJ1","wjmc":"理论课"}]}"#,
+        ),
+        "evaluationMethodSix/getRequiredReviewsData" => MockResponse::json(
+            r#"{"code":"200","msg":"成功","result":[{"rwid":"RW1","wjid":"WJ1","ypjcs":0,"sxz":"1","pjrdm":"mock","bpdm":"T1","bpmc":"张三","kcdm":"C1","kcmc":"软件工程","rwh":"1"}]}"#,
+        ),
+        _ => MockResponse::not_found(),
+    }
+}
+
+// ====================
+// Srs
+// ====================
+
+fn byxk(state: &mut State, req: &MockRequest, rest: &str) -> MockResponse {
+    let origin = req.origin();
+    match rest {
+        "xsxk/auth/cas" => {
+            if state.consume(req.query("ticket").as_deref()) {
+                let token = state.issue("SRS");
+                MockResponse::redirect(format!("{origin}/byxk/xsxk/profile/index.html"))
+                    .cookie("token", &token, "/byxk")
+            } else {
+                cas(req, &format!("{origin}/byxk/xsxk/auth/cas"))
+            }
+        }
+        "xsxk/profile/index.html" => MockResponse::ok(
+            r#"<html><script>var batch = {"code":"B2025-2026-1","name":"2025-2026学年第一学期选课"};</script></html>"#,
+        ),
+        _ => {
+            if !state.valid(req.header("authorization")) {
+                return MockResponse::json(
+                    r#"{"code":401,"msg":"认证失败，无法访问系统资源","data":null}"#,
+                );
+            }
+            match rest {
+                "xsxk/web/studentInfo" => MockResponse::json(
+                    r#"{"code":200,"msg":"操作成功","data":{"student":{"campus":"1","electiveBatchList":[{"code":"B2025-2026-1","name":"2025-2026学年第一学期选课","canSelect":"1","beginTime":"2025-09-01 08:00:00","endTime":"2025-09-14 18:00:00"}]}}}"#,
+                ),
+                _ => MockResponse::not_found(),
+            }
+        }
+    }
+}
+
+// ====================
+// Aas
+// ====================
+
+fn byxt(state: &mut State, req: &MockRequest, rest: &str) -> MockResponse {
+    let service = format!(
+        "{}/byxt/jwapp/sys/homeapp/index.do?contextPath=/jwapp",
+        req.origin()
+    );
+    if rest == "jwapp/sys/homeapp/index.do" {
+        if state.consume(req.query("ticket").as_deref()) {
+            let session = state.issue("GS");
+            return MockResponse::ok("<html>Aas</html>").cookie("GS_SESSIONID", &session, "/byxt");
+        }
+        if state.valid(req.cookie("GS_SESSIONID")) {
+            return MockResponse::ok("<html>Aas</html>");
+        }
+        return cas(req, &service);
+    }
+
+    // 会话失效时与真实服务一样转跳 SSO
+    if !state.valid(req.cookie("GS_SESSIONID")) {
+        return cas(req, &service);
+    }
+    match rest {
+        "jwapp/sys/homeapp/api/home/currentUser.do" => MockResponse::json(
+            r#"{"code":"0","msg":null,"datas":{"welcomeInfo":{"classWeek":1,"xnxqdm":"2025-2026-1"}}}"#,
+        ),
+        "jwapp/sys/homeapp/api/home/student/getMyScheduleDetail.do" => MockResponse::json(
+            r#"{"code":"0","msg":null,"datas":{"arrangedList":[],"notArrangeList":[]}}"#,
+        ),
+        _ => MockResponse::not_found(),
+    }
+}
+
+// ====================
+// App
+// ====================
+
+fn app(state: &mut State, req: &MockRequest, rest: &str) -> MockResponse {
+    let origin = req.origin();
+    let login = format!("{origin}/app/uc/wap/minigram/cas-login?login_from=xiaochengxu");
+    match rest {
+        "uc/wap/minigram/cas-login" => {
+            if req.query("redirect").is_some() {
+                return MockResponse::ok("<html>App</html>");
+            }
+            if state.consume(req.query("ticket").as_deref()) {
+                let session = state.issue("EAI");
+                let redirect = utils::url_encode(&format!("{origin}/app/site/center/personal"));
+                return MockResponse::redirect(format!(
+                    "{origin}/app/uc/wap/minigram/cas-login?redirect={redirect}&login_from=xiaochengxu"
+                ))
+                .cookie("eai-sess", &session, "/app");
+            }
+            cas(req, &login)
+        }
+        "exam/wap/default/index" => {
+            if !state.valid(req.cookie("eai-sess")) {
+                return cas(req, &login);
+            }
+            // 与真实页面相同, JSON 嵌在 HTML 里
+            MockResponse::ok(
+                "<html><script>\n    new Vue({\n        data: {\"2025-2026-1\":[{\"course_name\":\"工科数学分析\",\"exame_start_time\":\"2026-01-10 08:00:00\",\"exame_end_time\":\"2026-01-10 10:00:00\",\"location\":\"主M101\"}]},\n    });\n</script></html>",
+            )
+        }
+        _ => MockResponse::not_found(),
+    }
+}
+
+// ====================
+// User
+// ====================
+
+fn uc(state: &mut State, req: &MockRequest, rest: &str) -> MockResponse {
+    let origin = req.origin();
+    match rest {
+        "" => MockResponse::ok("<html>User Center</html>"),
+        "api/uc/status" => {
+            let session = req.cookie("UC_JSESSIONID");
+            if state.valid(session) {
+                return MockResponse::json(r#"{"code":0,"msg":"success","data":{"isLogin":true}}"#);
+            }
+            let res = MockResponse::json(r#"{"code":0,"msg":"success","data":{"isLogin":false}}"#);
+            match session {
+                Some(_) => res,
+                None => res.cookie("UC_JSESSIONID", &state.id("UC"), "/uc"),
+            }
+        }
+        "api/login" => {
+            let target = req.query("target").unwrap_or(format!("{origin}/uc/"));
+            cas(
+                req,
+                &format!("{origin}/uc/api/cas?target={}", utils::url_encode(&target)),
+            )
+        }
+        "api/cas" => {
+            // 票据有效时激活当前 JSESSIONID
+            match req.cookie("UC_JSESSIONID") {
+                Some(s) if state.consume(req.query("ticket").as_deref()) => {
+                    state.sessions.insert(s.to_string());
+                    let target = req.query("target").unwrap_or(format!("{origin}/uc/"));
+                    MockResponse::redirect(target)
+                }
+                _ => MockResponse::new(401, "Invalid ticket"),
+            }
+        }
+        _ => MockResponse::not_found(),
+    }
+}
+
+// ====================
+// Live
+// ====================
+
+fn yjapi(state: &mut State, req: &MockRequest, rest: &str) -> MockResponse {
+    match rest {
+        "casapi/index.php" => {
+            if !state.consume(req.query("ticket").as_deref()) {
+                return cas(req, req.url.as_str());
+            }
+            let token = state.issue("LIVE");
+            // PHP 序列化数组, URL 编码后放进 Cookie
+            let php = format!(
+                "a:2:{{i:0;s:6:\"_token\";i:1;s:{}:\"{token}\";}}",
+                token.len()
+            );
+            let forward = req.query("forward").unwrap_or_default();
+            MockResponse::redirect(format!("{forward}/")).cookie(
+                "_token",
+                &utils::url_encode(&php),
+                "/yjapi",
+            )
+        }
+        "courseapi/v2/schedule/get-week-schedules" => {
+            if !state.valid(bearer(req)) {
+                return MockResponse::json(
+                    r#"{"success":true,"result":{"code":401,"msg":"请重新登录","list":[]}}"#,
+                );
+            }
+            MockResponse::json(
+                r#"{"success":true,"result":{"code":200,"msg":"","list":[{"course":[{"course_id":"1","id":"L1","course_title":"软件工程","teacher_name":"张三"}]},{"course":[]},{"course":[]},{"course":[]},{"course":[]},{"course":[]},{"course":[]}]}}"#,
+            )
+        }
+        _ => MockResponse::not_found(),
+    }
+}
+
+// ====================
+// Cloud
+// ====================
+
+fn bhpan(state: &mut State, req: &MockRequest, rest: &str) -> MockResponse {
+    let origin = req.origin();
+    let callback = |code: &str| {
+        MockResponse::redirect(format!(
+            "{origin}/bhpan/anyshare/oauth2/login/callback?code={code}"
+        ))
+    };
+    let hydra = req.cookie("ory_hydra_session");
+    match rest {
+        "anyshare/oauth2/login" => {
+            if state.valid(hydra) {
+                callback(&state.id("code"))
+            } else {
+                let challenge = state.id("challenge");
+                MockResponse::redirect(format!(
+                    "{origin}/bhpan/oauth2/signin?login_challenge={challenge}"
+                ))
+            }
+        }
+        "oauth2/signin" => match req.query("ticket") {
+            None => MockResponse::ok("<html>Sign In</html>"),
+            Some(t) if req.cookie("login_challenge").is_some() && state.consume(Some(&t)) => {
+                let session = state.issue("HYDRA");
+                callback(&state.id("code")).cookie("ory_hydra_session", &session, "/bhpan")
+            }
+            Some(_) => MockResponse::new(401, "Invalid ticket"),
+        },
+        "anyshare/oauth2/login/callback" | "anyshare/oauth2/login/refreshToken" => {
+            if !state.valid(hydra) {
+                return MockResponse::new(401, "Invalid session");
+            }
+            let token = state.issue("CLOUD");
+            MockResponse::ok("<html>Cloud</html>").cookie("client.oauth2_token", &token, "/bhpan")
+        }
+        "api/efast/v1/owned-doc-lib" | "api/efast/v1/entry-doc-lib" => {
+            if !state.valid(bearer(req)) {
+                return MockResponse::new(
+                    401,
+                    r#"{"cause":"token 已过期","code":401001001,"message":"授权无效"}"#,
+                )
+                .header("Content-Type", "application/json");
+            }
+            MockResponse::json(r#"[{"id":"gns://0123456789ABCDEF","name":"Mock"}]"#)
+        }
+        _ => MockResponse::not_found(),
+    }
+}
@@ -0,0 +1,714 @@
+#[cfg(test)]
+mod tests {
+    use crate::mock::MockServer;
+    use crate::request::{Layer, Request, Scope};
+    use crate::utils::time::DateTime;
+    use crate::{Context, ContextPool};
+
+    fn setup() -> (MockServer, Context) {
+        let server = MockServer::start("127.0.0.1:0").unwrap();
+        let context = server.builder().build();
+        context.set_account(MockServer::USERNAME, MockServer::PASSWORD);
+        (server, context)
+    }
+
+    #[tokio::test]
+    async fn test_mock_sso() {
+        let (server, context) = setup();
+        context.login().await.unwrap();
+        // TGC 有效时直接转跳用户中心
+        context.login().await.unwrap();
+
+        let (_, context) = setup();
+        context.set_password("wrong");
+        assert!(context.login().await.is_err());
+
+        server.set_risk(true);
+        let context = server.builder().build();
+        context.set_account(MockServer::USERNAME, MockServer::PASSWORD);
+        context.login().await.unwrap();
+    }
+
+    #[test]
+    fn test_mock_shutdown() {
+        let server = MockServer::start("127.0.0.1:0").unwrap();
+        let addr = server.addr();
+        drop(server);
+        assert!(std::net::TcpStream::connect(addr).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mock_boya() {
+        let (server, context) = setup();
+        let boya = context.boya();
+        let semester = boya.get_semester().await.unwrap();
+        assert_eq!(2025, semester.start.date().year());
+        boya.select_course(1).await.unwrap();
+
+        // 服务端会话失效, 本地凭据仍未过期, 由响应触发重新登录
+        server.expire_sessions();
+        boya.get_semester().await.unwrap();
+        assert_eq!(2, server.sso_logins());
+
+        server.set_course_full(true);
+        let err = boya.select_course(1).await.unwrap_err();
+        assert!(matches!(
+            err.code(),
+            Some(crate::error::Code::BoyaCourseFull)
+        ));
+        assert!(!err.is_auth() && !err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_mock_tes() {
+        let (server, context) = setup();
+        let tes = context.tes();
+        let tasks = tes.get_task().await.unwrap();
+        assert_eq!(1, tasks.len());
+
+        // 会话失效后被重定向到 SSO 登录页, 重新登录后再试一次
+        server.expire_sessions();
+        tes.get_task().await.unwrap();
+        assert_eq!(2, server.sso_logins());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn test_mock_tracing() {
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+        use tracing_core::span::Current;
+
+        type Fields = Vec<(&'static str, String)>;
+
+        // 只收集各个 span 上记录的字段, 单线程运行时下用一个栈跟踪当前 span
+        #[derive(Clone, Default)]
+        struct Collector {
+            spans: Arc<Mutex<Vec<(&'static Metadata<'static>, Fields)>>>,
+            stack: Arc<Mutex<Vec<Id>>>,
+        }
+        struct Visitor<'a>(&'a mut Fields);
+        impl Visit for Visitor<'_> {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                self.0.push((field.name(), format!("{value:?}")));
+            }
+        }
+        impl Subscriber for Collector {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, span: &Attributes<'_>) -> Id {
+                let mut spans = self.spans.lock().unwrap();
+                spans.push((span.metadata(), Vec::new()));
+                Id::from_u64(spans.len() as u64)
+            }
+            fn record(&self, span: &Id, values: &Record<'_>) {
+                let mut spans = self.spans.lock().unwrap();
+                values.record(&mut Visitor(&mut spans[span.into_u64() as usize - 1].1));
+            }
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, _: &Event<'_>) {}
+            fn enter(&self, span: &Id) {
+                self.stack.lock().unwrap().push(span.clone());
+            }
+            fn exit(&self, _: &Id) {
+                self.stack.lock().unwrap().pop();
+            }
+            fn current_span(&self) -> Current {
+                match self.stack.lock().unwrap().last() {
+                    Some(id) => {
+                        let meta = self.spans.lock().unwrap()[id.into_u64() as usize - 1].0;
+                        Current::new(id.clone(), meta)
+                    }
+                    None => Current::none(),
+                }
+            }
+        }
+
+        let collector = Collector::default();
+        let _guard = tracing::subscriber::set_default(collector.clone());
+        let (server, context) = setup();
+        context.tes().get_task().await.unwrap();
+        server.expire_sessions();
+        context.tes().get_task().await.unwrap();
+
+        let spans = collector.spans.lock().unwrap();
+        let (_, fields) = spans
+            .iter()
+            .rfind(|(meta, _)| meta.name() == "tes.get_task")
+            .unwrap();
+        let field = |name: &str| {
+            fields
+                .iter()
+                .rfind(|(n, _)| *n == name)
+                .map(|(_, v)| v.as_str())
+        };
+        assert!(field("url").unwrap().contains("/spoc/pjxt/"));
+        assert_eq!(Some("200"), field("status"));
+        assert_eq!(Some("0"), field("retries"));
+        assert!(field("elapsed_ms").is_some());
+        assert_eq!(Some("\"Tes\""), field("relogin"));
+        // 登录过程中的请求记录在登录自己的 span 上
+        assert!(spans.iter().any(|(meta, fields)| {
+            meta.name() == "sso.service_login"
+                && fields
+                    .iter()
+                    .any(|(n, v)| *n == "url" && v.contains("/sso/login?service="))
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_mock_record_boya() {
+        let server = MockServer::start("127.0.0.1:0").unwrap();
+        let dir = std::env::temp_dir().join(format!("buaa_api_boya_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let context = server.builder().record(&dir).build();
+        context.set_account(MockServer::USERNAME, MockServer::PASSWORD);
+        context.boya().get_semester().await.unwrap();
+        context.boya().get_semester().await.unwrap();
+        // 每次请求的 AES 密钥都是随机的, 随 fixture 保存
+        let secrets: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|e| std::fs::read_to_string(e.unwrap().path()).ok())
+            .filter_map(|f| {
+                let v: serde_json::Value = serde_json::from_str(&f).ok()?;
+                v["secret"].as_str().map(str::to_string)
+            })
+            .collect();
+        assert_eq!(2, secrets.len());
+        assert_ne!(secrets[0], secrets[1]);
+
+        // 回放时取回录制的密钥才能解密响应
+        let context = server.builder().replay(&dir).build();
+        context.set_account(MockServer::USERNAME, MockServer::PASSWORD);
+        let semester = context.boya().get_semester().await.unwrap();
+        assert_eq!(2025, semester.start.date().year());
+        context.boya().get_semester().await.unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_single_flight() {
+        let (server, context) = setup();
+        let boya = context.boya();
+        let tasks = (0..8).map(|_| boya.get_semester());
+        let res = futures::future::join_all(tasks).await;
+        assert!(res.iter().all(|r| r.is_ok()));
+        assert_eq!(1, server.sso_logins());
+
+        // 等待者拿到的是领头者的错误, 而不是笼统的失败
+        let (server, context) = setup();
+        server.set_locked(true);
+        let tasks = (0..4).map(|_| context.login());
+        let res = futures::future::join_all(tasks).await;
+        assert!(res.iter().all(|r| matches!(
+            r.as_ref().unwrap_err().code(),
+            Some(crate::error::Code::AuthLocked)
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_mock_pool() {
+        let server = MockServer::start("127.0.0.1:0").unwrap();
+        let builder = server.builder();
+        let mut pool = ContextPool::with_builder(builder.clone());
+        pool.add(MockServer::USERNAME, MockServer::PASSWORD);
+        pool.add("other", "wrong");
+        let res = pool
+            .run(2, |c| async move { c.boya().get_semester().await })
+            .await;
+        assert!(res[MockServer::USERNAME].is_ok());
+        assert!(res["other"].is_err());
+
+        let dir = std::env::temp_dir().join(format!("buaa-pool-{}", std::process::id()));
+        pool.save_auth(&dir).unwrap();
+        let mut loaded = ContextPool::with_builder(builder);
+        loaded.load_auth(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(2, loaded.len());
+        // 复用保存的 SSO 会话, 不再登录
+        let context = loaded.get(MockServer::USERNAME).unwrap();
+        context.boya().get_semester().await.unwrap();
+        assert_eq!(1, server.sso_logins());
+    }
+
+    #[derive(Default)]
+    struct Audit(std::sync::Mutex<Vec<String>>);
+
+    impl Layer for std::sync::Arc<Audit> {
+        fn on_request(&self, scope: &Scope, req: &mut Request) {
+            req.headers.insert("X-Audit", "1".parse().unwrap());
+            if req.url.path().ends_with("/choseCourse") {
+                let log = format!("{} {:?} select", scope.group, scope.username);
+                self.0.lock().unwrap().push(log);
+            }
+        }
+
+        fn on_login(&self, scope: &Scope) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{} login", scope.group));
+        }
+
+        fn on_error(&self, scope: &Scope, _: &crate::Error) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{} error", scope.group));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_layer() {
+        let server = MockServer::start("127.0.0.1:0").unwrap();
+        let audit = std::sync::Arc::new(Audit::default());
+        let context = server.builder().layer(audit.clone()).build();
+        context.set_account(MockServer::USERNAME, MockServer::PASSWORD);
+        context.boya().select_course(1).await.unwrap();
+        server.expire_sessions();
+        context.set_password("wrong");
+        assert!(context.login().await.is_err());
+
+        let log = audit.0.lock().unwrap();
+        assert_eq!(
+            *log,
+            [
+                "Sso login",
+                "Boya login",
+                "Boya Some(\"mock\") select",
+                "Sso error"
+            ]
+        );
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_mock_blocking() {
+        let server = MockServer::start("127.0.0.1:0").unwrap();
+        let context = server.builder().build();
+        let context = crate::blocking::Context::from(context);
+        context.set_account(MockServer::USERNAME, MockServer::PASSWORD);
+        context.login().unwrap();
+        let semester = context.boya().get_semester().unwrap();
+        assert_eq!(2025, semester.start.date().year());
+        assert_eq!(1, context.class().query_course("2025-20261").unwrap().len());
+    }
+
+    #[tokio::test]
+    async fn test_mock_storage() {
+        use crate::store::storage::{AuthStorage, BundleStorage, MemoryStorage};
+        use std::sync::Arc;
+
+        let server = MockServer::start("127.0.0.1:0").unwrap();
+        let storage = Arc::new(MemoryStorage::new());
+        let builder = server.builder().storage(storage.clone()).autosave(true);
+        let context = builder.clone().build();
+        context.set_account(MockServer::USERNAME, MockServer::PASSWORD);
+        context.boya().get_semester().await.unwrap();
+        assert!(!storage.is_empty());
+
+        // 新的 Context 从存储恢复会话, 不再登录
+        let context = builder.build();
+        context.boya().get_semester().await.unwrap();
+        assert_eq!(1, server.sso_logins());
+
+        let path = std::env::temp_dir().join(format!("buaa-bundle-{}.json", std::process::id()));
+        let bundle = BundleStorage::new(&path);
+        context.save_to(&bundle).unwrap();
+        let (_, cred) = bundle.load().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(Some(MockServer::USERNAME), cred.username.as_deref());
+    }
+
+    #[tokio::test]
+    async fn test_mock_class() {
+        let (server, context) = setup();
+        let class = context.class();
+        let schedule = class
+            .query_schedule(
+                &DateTime::from_calendar(2025, crate::time::Month::September, 1, 0, 0, 0).unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!("软件工程", schedule[0].name);
+        let courses = class.query_course("2025-20261").await.unwrap();
+        assert_eq!(1, courses.len());
+        class.checkin(&schedule[0].id).await.unwrap();
+
+        server.expire_sessions();
+        assert_eq!(1, class.query_course("2025-20261").await.unwrap().len());
+    }
+
+    #[tokio::test]
+    async fn test_mock_groups() {
+        let (server, context) = setup();
+        assert_eq!(1, context.aas().get_config().await.unwrap().week);
+        assert_eq!(1, context.app().get_exam().await.unwrap().data.len());
+        assert_eq!("Mock", context.cloud().get_user_dir().await.unwrap().name);
+        assert_eq!(
+            1,
+            context
+                .live()
+                .get_week_schedule(&crate::time::Week::current())
+                .await
+                .unwrap()[0]
+                .len()
+        );
+        assert_eq!("2025-20261", context.spoc().get_week().await.unwrap().term);
+        assert_eq!(1, context.srs().get_config().await.unwrap().batchs.len());
+        context.tes().login().await.unwrap();
+        context.user().login().await.unwrap();
+        assert!(context.user().get_state().await.unwrap().contains("true"));
+
+        server.fail_next("/spoc/spocnewht/inco/ht/queryOne", 502);
+        assert!(context.spoc().get_week().await.is_err());
+        assert!(context.spoc().get_week().await.is_ok());
+
+        // 所有会话失效后各组都能重新登录
+        server.expire_sessions();
+        context.aas().get_config().await.unwrap();
+        context.app().get_exam().await.unwrap();
+        context.cloud().get_user_dir().await.unwrap();
+        context.spoc().get_week().await.unwrap();
+        context.srs().get_config().await.unwrap();
+        let live = context.live();
+        live.get_week_schedule(&crate::time::Week::current())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_webvpn() {
+        use reqwest::Url;
+
+        let server = MockServer::start("127.0.0.1:0").unwrap();
+        // 不指定门户时使用官方地址
+        let context = server.builder().webvpn(true).build();
+        let portal = crate::Endpoints::WEBVPN_PORTAL;
+        assert_eq!(Some(portal), context.endpoints.webvpn.as_deref());
+        assert!(
+            server
+                .builder()
+                .webvpn(false)
+                .build()
+                .endpoints
+                .webvpn
+                .is_none()
+        );
+
+        let portal = format!("{}/webvpn", server.base_url());
+        let context = server.builder().webvpn(true).webvpn_portal(&portal).build();
+        context.set_account(MockServer::USERNAME, MockServer::PASSWORD);
+        assert!(context.endpoints.byxk.starts_with(&portal));
+
+        context.login().await.unwrap();
+        assert!(context.sso().probe().await.unwrap());
+        assert_eq!(1, context.srs().get_config().await.unwrap().batchs.len());
+        assert_eq!("Mock", context.cloud().get_user_dir().await.unwrap().name);
+        let live = context.live();
+        live.get_week_schedule(&crate::time::Week::current())
+            .await
+            .unwrap();
+
+        // Cookie 都在门户域名下, 按服务的路径区分
+        let cookies = context.get_cookies();
+        let byxk = Url::parse(&context.endpoints.byxk).unwrap();
+        let bhpan = Url::parse(&context.endpoints.bhpan).unwrap();
+        assert!(cookies.get_by_base(&byxk, "token").is_some());
+        assert!(cookies.get_by_base(&bhpan, "token").is_none());
+        assert!(cookies.get_by_base(&bhpan, "client.oauth2_token").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_mock_session_report() {
+        use crate::store::session::Probe;
+
+        let (server, context) = setup();
+        let report = context.session_report();
+        assert!(report.tokens.iter().all(|t| !t.is_valid()));
+        context.boya().get_semester().await.unwrap();
+        context.srs().get_config().await.unwrap();
+
+        let mut report = context.session_report();
+        let boya = report.get("Boya").unwrap();
+        assert!(boya.has_value && boya.is_valid());
+        assert!(!report.get("Sso").unwrap().has_value);
+        assert!(report.get("Aas").unwrap().expires_at.is_none());
+        report.verify(&context).await;
+        assert!(matches!(
+            report.get("Boya").unwrap().verified,
+            Some(Probe::Valid)
+        ));
+        assert!(matches!(
+            report.get("Sso").unwrap().verified,
+            Some(Probe::Valid)
+        ));
+        assert!(report.get("Aas").unwrap().verified.is_none());
+
+        // 服务端会话失效, Boya 重新登录时顺带刷新了 SSO
+        server.expire_sessions();
+        let mut report = context.session_report();
+        report.verify(&context).await;
+        assert!(matches!(
+            report.get("Boya").unwrap().verified,
+            Some(Probe::Renewed)
+        ));
+        assert!(matches!(
+            report.get("Srs").unwrap().verified,
+            Some(Probe::Renewed)
+        ));
+        assert!(matches!(
+            report.get("Sso").unwrap().verified,
+            Some(Probe::Valid)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_mock_keepalive() {
+        use crate::KeepAlive;
+        use crate::api::{Boya, Srs};
+        use crate::request::sleep;
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        let (server, context) = setup();
+        let context = Arc::new(context);
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let log = errors.clone();
+        let keepalive = KeepAlive::new(Duration::from_secs(60))
+            .group::<Boya>()
+            .on_error(move |g, _| log.lock().unwrap().push(g.to_string()));
+
+        // 从未登录的组到期时间为 0, 立即刷新
+        let handle = context.spawn_keepalive(keepalive.clone().group::<Srs>(), |task| {
+            tokio::spawn(task);
+        });
+        let valid = |g: &str| context.session_report().get(g).unwrap().is_valid();
+        for _ in 0..50 {
+            if valid("Boya") && valid("Srs") {
+                break;
+            }
+            sleep(Duration::from_millis(20)).await;
+        }
+        assert!(valid("Boya") && valid("Srs"));
+        handle.stop();
+
+        server.expire_sessions();
+        context.set_password("wrong");
+        context.cred.update(|c| c.expire::<crate::api::Boya>());
+        let handle = context.spawn_keepalive(keepalive, |task| {
+            tokio::spawn(task);
+        });
+        for _ in 0..50 {
+            if !errors.lock().unwrap().is_empty() {
+                break;
+            }
+            sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(*errors.lock().unwrap(), ["Boya"]);
+        assert!(handle.is_running());
+        drop(handle);
+    }
+
+    #[tokio::test]
+    async fn test_mock_logout() {
+        let (server, context) = setup();
+        context.aas().login().await.unwrap();
+        let byxt = format!("{}/byxt/", server.base_url()).parse().unwrap();
+        assert!(
+            context
+                .get_cookies()
+                .get_by_url(&byxt, "GS_SESSIONID")
+                .is_some()
+        );
+
+        context.aas().reset();
+        let report = context.session_report();
+        assert!(!report.get("Aas").unwrap().is_valid());
+        assert!(report.get("Sso").unwrap().is_valid());
+        assert!(
+            context
+                .get_cookies()
+                .get_by_url(&byxt, "GS_SESSIONID")
+                .is_none()
+        );
+
+        // 注销后 TGC 被吊销, 只能用密码重新登录
+        context.sso().logout().await.unwrap();
+        assert!(!context.session_report().get("Sso").unwrap().is_valid());
+        assert!(!context.sso().probe().await.unwrap());
+        context.aas().login().await.unwrap();
+        assert_eq!(2, server.sso_logins());
+    }
+
+    #[tokio::test]
+    async fn test_mock_shared_host_reset() {
+        let (server, context) = setup();
+        context.spoc().get_week().await.unwrap();
+        context.tes().login().await.unwrap();
+        let pjxt = format!("{}/spoc/pjxt/", server.base_url()).parse().unwrap();
+        let session = |context: &Context| {
+            context
+                .get_cookies()
+                .get_by_url(&pjxt, "PJXT_SESSION")
+                .is_some()
+        };
+        assert!(session(&context));
+
+        // Tes 只清理自己路径下的 Cookie, Spoc 不受影响
+        context.tes().reset();
+        let report = context.session_report();
+        assert!(!report.get("Tes").unwrap().is_valid());
+        assert!(report.get("Spoc").unwrap().is_valid());
+        assert!(!session(&context));
+        context.spoc().get_week().await.unwrap();
+
+        // Spoc 清理整个主机, Tes 的会话随之失效
+        context.tes().login().await.unwrap();
+        context.spoc().reset();
+        assert!(!context.session_report().get("Tes").unwrap().is_valid());
+        assert!(!session(&context));
+        context.tes().get_task().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_service_login() {
+        let (server, context) = setup();
+        let service = format!("{}/bykc/sscv/cas/login", server.base_url());
+        let res = context.sso().service_login(&service).await.unwrap();
+        assert_eq!("/bykc/cas-login", res.final_url.path());
+        assert_eq!(2, res.redirects.len());
+        assert!(res.param("ticket").unwrap().starts_with("ST"));
+        assert!(res.param("token").unwrap().starts_with("BOYA"));
+        assert!(res.param("service").is_none());
+
+        // 本地仍认为有效但 TGC 已被吊销, 重新登录后再试一次
+        let logins = server.sso_logins();
+        context.sso().logout().await.unwrap();
+        context.cred.update(|c| c.refresh::<crate::api::Sso>());
+        let res = context.sso().service_login(&service).await.unwrap();
+        assert!(res.param("token").unwrap().starts_with("BOYA"));
+        assert_eq!(logins + 1, server.sso_logins());
+
+        // 没有账号时无法重新登录, 报告登录错误而不是重定向失败
+        let context = server.builder().build();
+        context.cred.update(|c| c.refresh::<crate::api::Sso>());
+        let err = context.sso().service_login(&service).await.unwrap_err();
+        assert!(matches!(
+            err.code(),
+            Some(crate::error::Code::AuthNoUsername)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_mock_challenge() {
+        use crate::api::sso::LoginChallenge;
+        use crate::error::Code;
+        use std::sync::{Arc, Mutex};
+
+        // 没有处理器时不作答直接提交, 被拒绝后按登录失败报告
+        let (server, context) = setup();
+        server.set_captcha(true);
+        server.set_sms(true);
+        let err = context.login().await.unwrap_err();
+        assert!(err.code().is_none());
+
+        let asked = Arc::new(Mutex::new(Vec::new()));
+        let log = asked.clone();
+        let context = server
+            .builder()
+            .challenge(move |c: LoginChallenge| {
+                let answer = match &c {
+                    LoginChallenge::Captcha { image } => {
+                        assert!(image.starts_with(b"\x89PNG"));
+                        MockServer::CAPTCHA
+                    }
+                    LoginChallenge::Sms { phone } => {
+                        assert_eq!(Some("138****0000"), phone.as_deref());
+                        MockServer::SMS_CODE
+                    }
+                };
+                log.lock().unwrap().push(c);
+                async move { Some(answer.to_string()) }
+            })
+            .build();
+        context.set_account(MockServer::USERNAME, MockServer::PASSWORD);
+        context.login().await.unwrap();
+        assert_eq!(1, server.sso_logins());
+        assert!(matches!(
+            asked.lock().unwrap()[..],
+            [LoginChallenge::Captcha { .. }, LoginChallenge::Sms { .. }]
+        ));
+
+        // 验证码一直答错, 用完次数后放弃
+        let context = server
+            .builder()
+            .challenge(|_| async { Some("0000".to_string()) })
+            .build();
+        context.set_account(MockServer::USERNAME, MockServer::PASSWORD);
+        let err = context.login().await.unwrap_err();
+        assert!(matches!(err.code(), Some(Code::AuthChallenge)));
+
+        // 密码错误时不再重复提交同一个密码
+        let asked = Arc::new(Mutex::new(0));
+        let count = asked.clone();
+        let context = server
+            .builder()
+            .challenge(move |_| {
+                *count.lock().unwrap() += 1;
+                async { Some(MockServer::CAPTCHA.to_string()) }
+            })
+            .build();
+        context.set_account(MockServer::USERNAME, "wrong");
+        let err = context.login().await.unwrap_err();
+        assert!(err.code().is_none());
+        assert_eq!(1, *asked.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_mock_login_report() {
+        use crate::api::sso::{LoginReport, LoginWarning};
+        use crate::error::Code;
+
+        let (server, context) = setup();
+        assert_eq!(
+            LoginReport::LoggedIn,
+            context.sso().login_report().await.unwrap()
+        );
+        assert_eq!(
+            LoginReport::Valid,
+            context.sso().login_report().await.unwrap()
+        );
+
+        server.expire_sessions();
+        server.set_weak_password(true);
+        assert_eq!(
+            LoginReport::Continued(LoginWarning::WeakPassword),
+            context.sso().login_report().await.unwrap()
+        );
+
+        // 拒绝自动继续时由调用方提示修改密码
+        server.expire_sessions();
+        let context = server.builder().auto_continue(false).build();
+        context.set_account(MockServer::USERNAME, MockServer::PASSWORD);
+        let err = context.login().await.unwrap_err();
+        assert!(matches!(err.code(), Some(Code::AuthWeakPassword)));
+
+        server.set_locked(true);
+        let err = context.login().await.unwrap_err();
+        assert!(matches!(err.code(), Some(Code::AuthLocked)));
+        assert!(err.is_auth() && !err.is_retryable());
+        server.set_locked(false);
+
+        context.set_password("wrong");
+        for _ in 0..5 {
+            assert!(context.login().await.unwrap_err().code().is_none());
+        }
+        let err = context.login().await.unwrap_err();
+        assert!(matches!(err.code(), Some(Code::AuthTooManyAttempts)));
+    }
+}
@@ -0,0 +1,170 @@
+//! Multi-account context pool
+
+use futures::{StreamExt, stream};
+
+use std::collections::BTreeMap;
+use std::future::Future;
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::error::Error;
+use crate::{Context, ContextBuilder};
+
+/// A set of [`Context`]s keyed by username
+///
+/// All contexts are built from the same [`ContextBuilder`] and share one HTTP transport (connection pool),
+/// while cookies and credentials are separate.
+///
+/// ## Example
+///
+/// ```no_run
+/// use buaa_api::ContextPool;
+///
+/// # async fn run() -> buaa_api::Result<()> {
+/// let mut pool = ContextPool::new();
+/// pool.add("username1", "password1");
+/// pool.add("username2", "password2");
+///
+/// // At most 4 accounts at a time
+/// let res = pool.run(4, |c| async move { c.tes().get_task().await }).await;
+/// for (username, tasks) in res {
+///     println!("{username}: {:?}", tasks.map(|t| t.len()));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct ContextPool {
+    builder: ContextBuilder,
+    contexts: BTreeMap<String, Arc<Context>>,
+}
+
+impl Default for ContextPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ContextPool {
+    /// Create an empty pool with default [`ContextBuilder`]
+    pub fn new() -> Self {
+        Self::with_builder(ContextBuilder::new())
+    }
+
+    /// Create an empty pool, every context is built from a clone of `builder`
+    ///
+    /// Cookies, credentials and storage set on `builder` are ignored
+    pub fn with_builder(builder: ContextBuilder) -> Self {
+        ContextPool {
+            builder: builder.share_transport(),
+            contexts: BTreeMap::new(),
+        }
+    }
+
+    fn build(&self) -> Context {
+        self.builder
+            .clone()
+            .cookies(Default::default())
+            .cred(Default::default())
+            .build()
+    }
+
+    /// Add an account and return its context. If the username exists, the old context is replaced
+    pub fn add(&mut self, username: &str, password: &str) -> Arc<Context> {
+        let context = self.build();
+        context.set_account(username, password);
+        let context = Arc::new(context);
+        self.contexts.insert(username.to_string(), context.clone());
+        context
+    }
+
+    /// Get the context of an account
+    pub fn get(&self, username: &str) -> Option<&Arc<Context>> {
+        self.contexts.get(username)
+    }
+
+    /// Remove an account
+    pub fn remove(&mut self, username: &str) -> Option<Arc<Context>> {
+        self.contexts.remove(username)
+    }
+
+    /// Iterate over usernames and contexts, sorted by username
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Arc<Context>)> {
+        self.contexts.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    /// Get the number of accounts
+    pub fn len(&self) -> usize {
+        self.contexts.len()
+    }
+
+    /// Whether the pool is empty
+    pub fn is_empty(&self) -> bool {
+        self.contexts.is_empty()
+    }
+
+    /// Run the same operation on every account, with at most `concurrency` running at a time
+    ///
+    /// Return the result of every account, keyed by username. `0` is treated as `1`
+    pub async fn run<F, Fut, T>(
+        &self,
+        concurrency: usize,
+        f: F,
+    ) -> BTreeMap<String, crate::Result<T>>
+    where
+        F: Fn(Arc<Context>) -> Fut,
+        Fut: Future<Output = crate::Result<T>>,
+    {
+        let tasks = self.contexts.iter().map(|(username, context)| {
+            let fut = f(context.clone());
+            async move { (username.clone(), fut.await) }
+        });
+        stream::iter(tasks)
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Load accounts from specified directory, one subdirectory per account named by username
+    ///
+    /// Each subdirectory uses the layout of [`Context::load_auth`]
+    #[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+    pub fn load_auth<P: AsRef<Path>>(&mut self, dir: P) -> crate::Result<()> {
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| Error::io("Failed to read pool directory").with_source(e))?;
+        for entry in entries {
+            let entry =
+                entry.map_err(|e| Error::io("Failed to read pool directory").with_source(e))?;
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let Some(username) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let context = self.build();
+            context.load_auth(&path)?;
+            // 目录名就是用户名, 文件中没有用户名时以目录名为准
+            if context.get_cred().username.is_none() {
+                context.set_username(username);
+            }
+            self.contexts
+                .insert(username.to_string(), Arc::new(context));
+        }
+        Ok(())
+    }
+
+    /// Save accounts to specified directory, one subdirectory per account named by username
+    ///
+    /// Each subdirectory uses the layout of [`Context::save_auth`]
+    #[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+    pub fn save_auth<P: AsRef<Path>>(&self, dir: P) -> crate::Result<()> {
+        for (username, context) in &self.contexts {
+            let path = dir.as_ref().join(username);
+            std::fs::create_dir_all(&path)
+                .map_err(|e| Error::io("Failed to create account directory").with_source(e))?;
+            context.save_auth(&path)?;
+        }
+        Ok(())
+    }
+}
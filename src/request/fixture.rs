@@ -0,0 +1,262 @@
+//! Record and replay transports for offline testing
+//!
+//! Every hop is saved as one JSON file, so redirects and `Set-Cookie` headers are kept in order.
+//! Request bodies are **not** saved because they may contain passwords,
+//! but response cookies, tokens and secrets like the Boya AES key are,
+//! so do not share fixtures of a real account.
+
+use base64::engine::{Engine, general_purpose};
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use reqwest::header::{HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{HeaderMap, Method, Request, Response, StatusCode, Transport, Url};
+use crate::error::Error;
+
+// 单个 fixture 文件的内容, 一跳请求对应一个文件
+#[derive(Debug, Serialize, Deserialize)]
+struct Fixture {
+    method: String,
+    url: String,
+    status: u16,
+    headers: Vec<(String, String)>,
+    // 能用 UTF-8 表示的响应体直接存文本, 方便手动修改, 否则存 Base64
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body_base64: Option<String>,
+    // 响应依赖的随机值, 见 Transport::fixture_secret
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    secret: Option<String>,
+}
+
+impl Fixture {
+    fn new(req: &Request, res: &Response, secret: Option<String>) -> Self {
+        let headers = res
+            .headers
+            .iter()
+            .filter_map(|(k, v)| v.to_str().ok().map(|v| (k.to_string(), v.to_string())))
+            .collect();
+        let (body, body_base64) = match std::str::from_utf8(&res.body) {
+            Ok(s) => (Some(s.to_string()), None),
+            Err(_) => (None, Some(general_purpose::STANDARD.encode(&res.body))),
+        };
+        Fixture {
+            method: req.method.to_string(),
+            url: req.url.to_string(),
+            status: res.status.as_u16(),
+            headers,
+            body,
+            body_base64,
+            secret,
+        }
+    }
+
+    fn into_response(self, url: Url) -> crate::Result<Response> {
+        let status = StatusCode::from_u16(self.status)
+            .map_err(|e| Error::parse("Invalid fixture status").with_source(e))?;
+        let mut headers = HeaderMap::new();
+        for (k, v) in self.headers {
+            let k = HeaderName::try_from(k)
+                .map_err(|e| Error::parse("Invalid fixture header").with_source(e))?;
+            let v = HeaderValue::try_from(v)
+                .map_err(|e| Error::parse("Invalid fixture header").with_source(e))?;
+            headers.append(k, v);
+        }
+        let body = match (self.body, self.body_base64) {
+            (Some(s), _) => Bytes::from(s),
+            (None, Some(b)) => general_purpose::STANDARD
+                .decode(b)
+                .map(Bytes::from)
+                .map_err(|e| Error::parse("Invalid fixture body").with_source(e))?,
+            (None, None) => Bytes::new(),
+        };
+        Ok(Response {
+            status,
+            headers,
+            url,
+            body,
+        })
+    }
+
+    // 忽略查询参数比较, 因为其中常有时间戳之类的随机值
+    fn matches_path(&self, method: &Method, url: &Url) -> bool {
+        self.method == method.as_str()
+            && Url::parse(&self.url)
+                .is_ok_and(|u| u.origin() == url.origin() && u.path() == url.path())
+    }
+}
+
+/// Transport that saves every hop passing through it to fixture files
+///
+/// Files are named `<index>-<METHOD>-<host>.json` in the order of responses.
+/// Use [`ReplayTransport`] on the same directory to serve them back.
+pub struct RecordTransport<T> {
+    inner: T,
+    dir: PathBuf,
+    index: AtomicUsize,
+    // 尚未收到响应的请求所用的随机值, 按方法和 URL 对应
+    secrets: Mutex<Vec<(String, String, String)>>,
+}
+
+impl<T: Transport> RecordTransport<T> {
+    /// Wrap a transport, fixtures will be written to `dir`
+    pub fn new<P: AsRef<Path>>(inner: T, dir: P) -> Self {
+        RecordTransport {
+            inner,
+            dir: dir.as_ref().to_path_buf(),
+            index: AtomicUsize::new(0),
+            secrets: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn save(&self, req: &Request, res: &Response) -> crate::Result<()> {
+        let secret = {
+            let mut secrets = self.secrets.lock().unwrap_or_else(|e| e.into_inner());
+            let method = req.method.as_str();
+            let url = req.url.as_str();
+            secrets
+                .iter()
+                .position(|(m, u, _)| m == method && u == url)
+                .map(|i| secrets.remove(i).2)
+        };
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| Error::io("Failed to create fixture dir").with_source(e))?;
+        let index = self.index.fetch_add(1, Ordering::Relaxed);
+        let host = req.url.host_str().unwrap_or("unknown");
+        let path = self
+            .dir
+            .join(format!("{index:04}-{}-{host}.json", req.method));
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+            .map_err(|e| Error::io("Failed to open fixture").with_source(e))?;
+        serde_json::to_writer_pretty(file, &Fixture::new(req, res, secret))
+            .map_err(|e| Error::io("Failed to write fixture").with_source(e))
+    }
+}
+
+impl<T: Transport> Transport for RecordTransport<T> {
+    fn execute(&self, req: Request) -> BoxFuture<'_, crate::Result<Response>> {
+        Box::pin(async move {
+            let res = self.inner.execute(req.clone()).await?;
+            self.save(&req, &res)?;
+            Ok(res)
+        })
+    }
+
+    fn is_fixture(&self) -> bool {
+        true
+    }
+
+    fn client(&self) -> Option<&reqwest::Client> {
+        self.inner.client()
+    }
+
+    fn fixture_secret(&self, method: &Method, url: &str, fresh: String) -> String {
+        let secret = self.inner.fixture_secret(method, url, fresh);
+        // 与 Request 中的 URL 保持同样的规范化形式
+        if let Ok(url) = Url::parse(url) {
+            let mut secrets = self.secrets.lock().unwrap_or_else(|e| e.into_inner());
+            secrets.push((method.to_string(), url.to_string(), secret.clone()));
+        }
+        secret
+    }
+}
+
+/// Transport that serves responses saved by [`RecordTransport`] without network
+///
+/// A request takes the first unused fixture with the same method and URL,
+/// or else the first one with the same method and URL path.
+/// So random query values like timestamps do not break replay.
+pub struct ReplayTransport {
+    dir: PathBuf,
+    // 懒加载, 未使用的 fixture 按文件名顺序排列
+    fixtures: Mutex<Option<Vec<Fixture>>>,
+}
+
+impl ReplayTransport {
+    /// Serve fixtures from `dir`, files are loaded on the first request
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        ReplayTransport {
+            dir: dir.as_ref().to_path_buf(),
+            fixtures: Mutex::new(None),
+        }
+    }
+
+    fn load(&self) -> crate::Result<Vec<Fixture>> {
+        let mut paths = fs::read_dir(&self.dir)
+            .map_err(|e| Error::io("Failed to read fixture dir").with_source(e))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|e| e == "json"))
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths
+            .into_iter()
+            .map(|path| {
+                let file = OpenOptions::new()
+                    .read(true)
+                    .open(&path)
+                    .map_err(|e| Error::io("Failed to open fixture").with_source(e))?;
+                serde_json::from_reader(file)
+                    .map_err(|e| Error::io("Failed to read fixture").with_source(e))
+            })
+            .collect()
+    }
+
+    fn with_fixtures<R>(&self, f: impl FnOnce(&mut Vec<Fixture>) -> R) -> crate::Result<R> {
+        let mut guard = self.fixtures.lock().unwrap_or_else(|e| e.into_inner());
+        if guard.is_none() {
+            *guard = Some(self.load()?);
+        }
+        Ok(f(guard.as_mut().expect("Fixtures should be loaded")))
+    }
+
+    fn find(fixtures: &[Fixture], method: &Method, url: &Url) -> Option<usize> {
+        fixtures
+            .iter()
+            .position(|f| f.method == method.as_str() && f.url == url.as_str())
+            .or_else(|| fixtures.iter().position(|f| f.matches_path(method, url)))
+    }
+
+    fn take(&self, req: &Request) -> crate::Result<Fixture> {
+        self.with_fixtures(|fixtures| {
+            let index = Self::find(fixtures, &req.method, &req.url).ok_or_else(|| {
+                Error::network("No fixture matched")
+                    .with_source(format!("{} {}", req.method, req.url))
+            })?;
+            Ok(fixtures.remove(index))
+        })?
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn execute(&self, req: Request) -> BoxFuture<'_, crate::Result<Response>> {
+        Box::pin(async move { self.take(&req)?.into_response(req.url) })
+    }
+
+    fn is_fixture(&self) -> bool {
+        true
+    }
+
+    // 只查看即将匹配到的 fixture, 随后的 execute 才会取走它
+    fn fixture_secret(&self, method: &Method, url: &str, fresh: String) -> String {
+        let Ok(url) = Url::parse(url) else {
+            return fresh;
+        };
+        self.with_fixtures(|fixtures| {
+            Self::find(fixtures, method, &url).and_then(|i| fixtures[i].secret.clone())
+        })
+        .ok()
+        .flatten()
+        .unwrap_or(fresh)
+    }
+}
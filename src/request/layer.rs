@@ -0,0 +1,97 @@
+//! Middleware hooks on requests, responses and logins
+
+use std::sync::Arc;
+
+use crate::error::Error;
+
+use super::{Request, Response};
+
+/// Where a hook is called from
+#[derive(Debug, Clone, Copy)]
+pub struct Scope<'a> {
+    /// API group, e.g. `Boya`. `Core` for requests made on the context itself
+    pub group: &'static str,
+    /// Username of the context, if set
+    pub username: Option<&'a str>,
+}
+
+/// Hooks to observe and modify traffic of a [`crate::Context`], set by [`crate::ContextBuilder::layer`]
+///
+/// Every method has an empty default, implement only what you need.
+/// Layers are called in the order they are added.
+///
+/// ## Example
+///
+/// ```
+/// use buaa_api::ContextBuilder;
+/// use buaa_api::request::{Layer, Request, Scope};
+///
+/// struct Audit;
+///
+/// impl Layer for Audit {
+///     fn on_request(&self, scope: &Scope, req: &mut Request) {
+///         if req.url.path().ends_with("/choseCourse") {
+///             println!("{:?} selected a course", scope.username);
+///         }
+///     }
+/// }
+///
+/// let context = ContextBuilder::new().layer(Audit).build();
+/// ```
+pub trait Layer: Send + Sync + 'static {
+    /// Called once before a request is sent, retries and redirects are not included
+    fn on_request(&self, scope: &Scope, req: &mut Request) {
+        let _ = (scope, req);
+    }
+
+    /// Called with the final response of a request, after redirects and retries
+    fn on_response(&self, scope: &Scope, res: &mut Response) {
+        let _ = (scope, res);
+    }
+
+    /// Called after a successful login, `scope.group` is the token just refreshed
+    fn on_login(&self, scope: &Scope) {
+        let _ = scope;
+    }
+
+    /// Called when a request fails to send, a login fails, or an API returns an error
+    fn on_error(&self, scope: &Scope, error: &Error) {
+        let _ = (scope, error);
+    }
+}
+
+// 按添加顺序依次调用
+#[derive(Clone, Default)]
+pub(crate) struct Layers(Vec<Arc<dyn Layer>>);
+
+impl Layers {
+    pub fn push(&mut self, layer: Arc<dyn Layer>) {
+        self.0.push(layer);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn on_request(&self, scope: &Scope, req: &mut Request) {
+        self.0.iter().for_each(|l| l.on_request(scope, req));
+    }
+
+    pub fn on_response(&self, scope: &Scope, res: &mut Response) {
+        self.0.iter().for_each(|l| l.on_response(scope, res));
+    }
+
+    pub fn on_login(&self, scope: &Scope) {
+        self.0.iter().for_each(|l| l.on_login(scope));
+    }
+
+    pub fn on_error(&self, scope: &Scope, error: &Error) {
+        self.0.iter().for_each(|l| l.on_error(scope, error));
+    }
+}
+
+// 标记类型的短名, 例如 buaa_api::api::Boya -> Boya
+pub(crate) fn group_name<G>() -> &'static str {
+    let name = std::any::type_name::<G>();
+    name.rsplit("::").next().unwrap_or(name)
+}
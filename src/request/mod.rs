@@ -0,0 +1,382 @@
+//! HTTP transport layer
+//!
+//! A [`Transport`] only sends one request and returns one response.
+//! Cookies and redirects are handled by [`crate::Context`] itself,
+//! so a fake transport for tests gets the same behavior as the default one.
+
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderName, HeaderValue, USER_AGENT};
+use serde::Serialize;
+
+use std::error::Error as StdError;
+use std::sync::Arc;
+
+use crate::error::Error;
+
+mod fixture;
+mod layer;
+mod retry;
+mod test;
+pub(crate) mod trace;
+
+pub use fixture::{RecordTransport, ReplayTransport};
+pub use layer::{Layer, Scope};
+pub(crate) use layer::{Layers, group_name};
+pub use reqwest::header::HeaderMap;
+pub use reqwest::{Client, Method, StatusCode, Url};
+pub use retry::RetryPolicy;
+pub(crate) use retry::{RetryPolicies, sleep};
+
+pub(crate) const UA: &[u8] = b"Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/130.0.0.0 Safari/537.36 Edg/130.0.0.0";
+
+/// A single HTTP request passed to [`Transport`]
+#[derive(Debug, Clone)]
+pub struct Request {
+    /// HTTP method
+    pub method: Method,
+    /// Target URL, including query
+    pub url: Url,
+    /// Request headers, `Cookie` is already included
+    pub headers: HeaderMap,
+    /// Request body
+    pub body: Option<Bytes>,
+}
+
+impl Request {
+    /// Create a request without headers and body
+    pub fn new(method: Method, url: Url) -> Self {
+        Request {
+            method,
+            url,
+            headers: HeaderMap::new(),
+            body: None,
+        }
+    }
+}
+
+/// A single HTTP response returned by [`Transport`]
+#[derive(Debug, Clone)]
+pub struct Response {
+    /// HTTP status code
+    pub status: StatusCode,
+    /// Response headers, including `Set-Cookie` and `Location`
+    pub headers: HeaderMap,
+    /// URL of this response. After redirects, it is the final URL
+    pub url: Url,
+    /// Response body
+    pub body: Bytes,
+}
+
+impl Response {
+    /// Get the status code
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Get the headers
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Get the URL. After redirects, it is the final URL
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Take the body as bytes
+    pub fn bytes(self) -> Bytes {
+        self.body
+    }
+
+    /// Take the body as text, invalid UTF-8 will be replaced
+    pub fn text(self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+/// HTTP transport used by [`crate::Context`]
+///
+/// Implementations must **not** follow redirects or manage cookies,
+/// `Context` does this for every hop.
+///
+/// ## Example
+///
+/// ```
+/// use buaa_api::ContextBuilder;
+/// use buaa_api::request::{HeaderMap, Request, Response, StatusCode, Transport};
+/// use bytes::Bytes;
+/// use futures::future::BoxFuture;
+///
+/// struct Fake;
+///
+/// impl Transport for Fake {
+///     fn execute(&self, req: Request) -> BoxFuture<'_, buaa_api::Result<Response>> {
+///         Box::pin(async move {
+///             Ok(Response {
+///                 status: StatusCode::OK,
+///                 headers: HeaderMap::new(),
+///                 url: req.url,
+///                 body: Bytes::from_static(b"{}"),
+///             })
+///         })
+///     }
+/// }
+///
+/// let context = ContextBuilder::new().transport(Fake).build();
+/// ```
+pub trait Transport: Send + Sync + 'static {
+    /// Send one request and return its response
+    fn execute(&self, req: Request) -> BoxFuture<'_, crate::Result<Response>>;
+
+    /// Whether responses are recorded or replayed as fixtures
+    ///
+    /// Random values that responses depend on go through [`Transport::fixture_secret`]
+    fn is_fixture(&self) -> bool {
+        false
+    }
+
+    /// The underlying `reqwest` client, `None` if this transport does not use one
+    fn client(&self) -> Option<&Client> {
+        None
+    }
+
+    /// Pick a random secret that the response of a request depends on, e.g. Boya AES key
+    ///
+    /// `fresh` is newly generated and returned by default.
+    /// [`RecordTransport`] saves it with the fixture of the request,
+    /// [`ReplayTransport`] returns the saved one, so the response can be decrypted again
+    fn fixture_secret(&self, method: &Method, url: &str, fresh: String) -> String {
+        let _ = (method, url);
+        fresh
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    fn execute(&self, req: Request) -> BoxFuture<'_, crate::Result<Response>> {
+        (**self).execute(req)
+    }
+
+    fn is_fixture(&self) -> bool {
+        (**self).is_fixture()
+    }
+
+    fn client(&self) -> Option<&Client> {
+        (**self).client()
+    }
+
+    fn fixture_secret(&self, method: &Method, url: &str, fresh: String) -> String {
+        (**self).fixture_secret(method, url, fresh)
+    }
+}
+
+/// Default [`Transport`] based on `reqwest`
+pub struct ReqwestTransport {
+    client: Client,
+}
+
+impl ReqwestTransport {
+    /// Create with default client
+    ///
+    /// Sometimes the SSL certificate may be invalid, set `tls` to disable the verification
+    pub fn new(tls: bool) -> Self {
+        let mut header = HeaderMap::new();
+        header.insert(
+            USER_AGENT,
+            HeaderValue::from_bytes(UA).expect("UA should always be valid"),
+        );
+
+        let client = Client::builder()
+            .danger_accept_invalid_certs(tls)
+            .default_headers(header)
+            // 重定向和 Cookie 由 Context 逐跳处理
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("Client should always be built successfully");
+        ReqwestTransport { client }
+    }
+
+    /// Create with a custom client
+    ///
+    /// The client should disable redirect and cookie store. And with UA below
+    ///
+    /// `Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/130.0.0.0 Safari/537.36 Edg/130.0.0.0`
+    pub fn with_client(client: Client) -> Self {
+        ReqwestTransport { client }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn execute(&self, req: Request) -> BoxFuture<'_, crate::Result<Response>> {
+        Box::pin(async move {
+            let mut builder = self
+                .client
+                .request(req.method, req.url)
+                .headers(req.headers);
+            if let Some(body) = req.body {
+                builder = builder.body(body);
+            }
+            let res = builder.send().await?;
+            let status = res.status();
+            let headers = res.headers().clone();
+            let url = res.url().clone();
+            let body = res.bytes().await?;
+            Ok(Response {
+                status,
+                headers,
+                url,
+                body,
+            })
+        })
+    }
+
+    fn client(&self) -> Option<&Client> {
+        Some(&self.client)
+    }
+}
+
+/// Request builder bound to a [`crate::Context`]
+///
+/// Obtain it via [`crate::Context::request`], errors are deferred to [`RequestBuilder::send`]
+pub struct RequestBuilder<'a, G> {
+    context: &'a crate::Context<G>,
+    req: crate::Result<Request>,
+    idempotent: Option<bool>,
+}
+
+impl<'a, G> RequestBuilder<'a, G> {
+    pub(crate) fn new(context: &'a crate::Context<G>, method: Method, url: &str) -> Self {
+        let req = Url::parse(url)
+            .map(|url| Request::new(method, url))
+            .map_err(|e| Error::parameter("Invalid URL").with_source(e));
+        RequestBuilder {
+            context,
+            req,
+            idempotent: None,
+        }
+    }
+
+    /// Add a header
+    pub fn header<K, V>(mut self, key: K, value: V) -> Self
+    where
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: StdError + Send + Sync + 'static,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: StdError + Send + Sync + 'static,
+    {
+        if let Ok(req) = &mut self.req {
+            let header = HeaderName::try_from(key)
+                .map_err(|e| Error::parameter("Invalid header name").with_source(e))
+                .and_then(|k| {
+                    HeaderValue::try_from(value)
+                        .map(|v| (k, v))
+                        .map_err(|e| Error::parameter("Invalid header value").with_source(e))
+                });
+            match header {
+                Ok((k, v)) => {
+                    req.headers.insert(k, v);
+                }
+                Err(e) => self.req = Err(e),
+            }
+        }
+        self
+    }
+
+    /// Add `Authorization: Bearer <token>` header
+    pub fn bearer_auth(self, token: &str) -> Self {
+        self.header(AUTHORIZATION, format!("Bearer {token}"))
+    }
+
+    /// Append URL query
+    pub fn query<T: Serialize + ?Sized>(mut self, query: &T) -> Self {
+        if let Ok(req) = &mut self.req {
+            let mut url = req.url.clone();
+            let res = {
+                let mut pairs = url.query_pairs_mut();
+                query
+                    .serialize(serde_urlencoded::Serializer::new(&mut pairs))
+                    .map(|_| ())
+            };
+            match res {
+                Ok(_) => {
+                    // 空查询会留下一个 '?'
+                    if url.query() == Some("") {
+                        url.set_query(None);
+                    }
+                    req.url = url;
+                }
+                Err(e) => self.req = Err(Error::parameter("Invalid query").with_source(e)),
+            }
+        }
+        self
+    }
+
+    /// Set `application/x-www-form-urlencoded` body
+    pub fn form<T: Serialize + ?Sized>(mut self, form: &T) -> Self {
+        if let Ok(req) = &mut self.req {
+            match serde_urlencoded::to_string(form) {
+                Ok(body) => {
+                    req.headers.insert(
+                        CONTENT_TYPE,
+                        HeaderValue::from_static("application/x-www-form-urlencoded"),
+                    );
+                    req.body = Some(Bytes::from(body));
+                }
+                Err(e) => self.req = Err(Error::parameter("Invalid form").with_source(e)),
+            }
+        }
+        self
+    }
+
+    /// Set `application/json` body
+    pub fn json<T: Serialize + ?Sized>(mut self, json: &T) -> Self {
+        if let Ok(req) = &mut self.req {
+            match serde_json::to_vec(json) {
+                Ok(body) => {
+                    req.headers
+                        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                    req.body = Some(Bytes::from(body));
+                }
+                Err(e) => self.req = Err(Error::parameter("Invalid JSON").with_source(e)),
+            }
+        }
+        self
+    }
+
+    /// Set raw body
+    pub fn body<B: Into<Bytes>>(mut self, body: B) -> Self {
+        if let Ok(req) = &mut self.req {
+            req.body = Some(body.into());
+        }
+        self
+    }
+
+    /// Mark whether the request can be safely sent more than once
+    ///
+    /// Only idempotent requests are retried on transient failures, see [`RetryPolicy`].
+    /// By default it depends on the method, so `POST` is not retried
+    pub fn idempotent(mut self, idempotent: bool) -> Self {
+        self.idempotent = Some(idempotent);
+        self
+    }
+
+    /// Send the request, following redirects and storing cookies
+    pub async fn send(self) -> crate::Result<Response> {
+        let req = self.req?;
+        let idempotent = self
+            .idempotent
+            .unwrap_or_else(|| req.method.is_idempotent());
+        self.context.send(req, idempotent).await
+    }
+
+    // 返回最终响应和途经的重定向地址, 用于 CAS 登录
+    pub(crate) async fn send_traced(self) -> crate::Result<(Response, Vec<Url>)> {
+        let req = self.req?;
+        let idempotent = self
+            .idempotent
+            .unwrap_or_else(|| req.method.is_idempotent());
+        let mut hops = Vec::new();
+        let res = self.context.send_traced(req, idempotent, &mut hops).await?;
+        Ok((res, hops))
+    }
+}
@@ -0,0 +1,139 @@
+//! Retry policy for transient failures
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::crypto::rand::{Rng, WyRng};
+
+use super::{Response, StatusCode};
+
+/// Retry policy for transient network and server failures
+///
+/// Only idempotent requests are retried, see [`super::RequestBuilder::idempotent`].
+/// The delay before the `n`-th retry is `base_delay * 2^(n-1)`, capped by `max_delay`,
+/// with "full jitter" if enabled.
+///
+/// ## Example
+///
+/// ```
+/// use std::time::Duration;
+/// use buaa_api::ContextBuilder;
+/// use buaa_api::api::Class;
+/// use buaa_api::request::RetryPolicy;
+///
+/// let context = ContextBuilder::new()
+///     .retry(RetryPolicy::default().max_attempts(2))
+///     // iClass is flaky, try harder
+///     .retry_for::<Class>(RetryPolicy::default().max_attempts(5).base_delay(Duration::from_millis(500)))
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Set max attempts, including the first one. `0` is treated as `1`
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set the delay before the first retry
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Set the upper bound of delay
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Whether to randomize the delay in `[0, delay]`
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Whether the result of an attempt is worth retrying
+    ///
+    /// - Network errors, except DNS failure which almost means not connected to BUAA-WiFi
+    /// - `502 Bad Gateway`, `503 Service Unavailable` and `504 Gateway Timeout`
+    pub fn is_transient(res: &crate::Result<Response>) -> bool {
+        match res {
+            Ok(res) => matches!(
+                res.status,
+                StatusCode::BAD_GATEWAY
+                    | StatusCode::SERVICE_UNAVAILABLE
+                    | StatusCode::GATEWAY_TIMEOUT
+            ),
+            Err(e) => e.is_retryable(),
+        }
+    }
+
+    /// Whether another attempt is allowed after `attempt` finished attempts
+    pub(crate) fn allows(&self, attempt: u32) -> bool {
+        attempt < self.max_attempts
+    }
+
+    /// Delay before the next attempt, `attempt` is the number of finished attempts
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        // 防止移位溢出, 反正早就超过上限了
+        let factor = 1u32 << attempt.saturating_sub(1).min(16);
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        if self.jitter && !delay.is_zero() {
+            let millis = delay.as_millis() as u64;
+            Duration::from_millis(WyRng::new().random_range(0..=millis))
+        } else {
+            delay
+        }
+    }
+}
+
+// 默认策略和按 API 组覆盖的策略, 组以标记类型的名字为键
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RetryPolicies {
+    pub default: RetryPolicy,
+    pub groups: HashMap<&'static str, RetryPolicy>,
+}
+
+impl RetryPolicies {
+    pub fn get<G>(&self) -> &RetryPolicy {
+        self.groups
+            .get(std::any::type_name::<G>())
+            .unwrap_or(&self.default)
+    }
+}
+
+// 不依赖异步运行时的计时器, 原生平台由 futures-timer 的单个后台线程驱动,
+// WASM 上使用 setTimeout. 提前丢弃即取消, 不会留下等待中的线程
+pub(crate) async fn sleep(delay: Duration) {
+    if delay.is_zero() {
+        return;
+    }
+    futures_timer::Delay::new(delay).await;
+}
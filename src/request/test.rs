@@ -0,0 +1,176 @@
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use futures::future::BoxFuture;
+    use reqwest::header::{COOKIE, HeaderValue, LOCATION, SET_COOKIE};
+
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use crate::ContextBuilder;
+    use crate::api::Class;
+    use crate::request::*;
+
+    // 首跳设置 Cookie 并重定向, 次跳只有带上 Cookie 才返回 ok
+    struct Fake;
+
+    impl Transport for Fake {
+        fn execute(&self, req: Request) -> BoxFuture<'_, crate::Result<Response>> {
+            Box::pin(async move {
+                let mut headers = HeaderMap::new();
+                let (status, body) = match req.url.path() {
+                    "/start" => {
+                        headers.insert(LOCATION, HeaderValue::from_static("/next"));
+                        headers.insert(SET_COOKIE, HeaderValue::from_static("session=1; Path=/"));
+                        (StatusCode::FOUND, "")
+                    }
+                    "/next" if req.headers.get(COOKIE).is_some_and(|c| c == "session=1") => {
+                        (StatusCode::OK, "ok")
+                    }
+                    _ => (StatusCode::FORBIDDEN, "denied"),
+                };
+                Ok(Response {
+                    status,
+                    headers,
+                    url: req.url,
+                    body: Bytes::from_static(body.as_bytes()),
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_replay() {
+        let dir = std::env::temp_dir().join(format!("buaa_api_fixture_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let context = ContextBuilder::new().transport(Fake).record(&dir).build();
+        assert!(context.transport().is_fixture());
+        let res = context
+            .get("https://a.test/start?t=1")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!("ok", res.text());
+        assert_eq!(2, std::fs::read_dir(&dir).unwrap().count());
+
+        // 查询参数不同也能按路径匹配
+        let context = ContextBuilder::new().replay(&dir).build();
+        let res = context
+            .get("https://a.test/start?t=2")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!("https://a.test/next", res.url().as_str());
+        assert_eq!("ok", res.text());
+        assert!(context.get_cookies().get("a.test", "session").is_some());
+
+        // fixture 用完后不再匹配
+        let res = context.get("https://a.test/start").send().await;
+        assert!(res.is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // 前两次返回 503, 之后返回 ok
+    struct Flaky(Arc<AtomicUsize>);
+
+    impl Transport for Flaky {
+        fn execute(&self, req: Request) -> BoxFuture<'_, crate::Result<Response>> {
+            Box::pin(async move {
+                let status = match self.0.fetch_add(1, Ordering::SeqCst) {
+                    0 | 1 => StatusCode::SERVICE_UNAVAILABLE,
+                    _ => StatusCode::OK,
+                };
+                Ok(Response {
+                    status,
+                    headers: HeaderMap::new(),
+                    url: req.url,
+                    body: Bytes::new(),
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let policy = RetryPolicy::default().base_delay(Duration::ZERO);
+        let context = ContextBuilder::new()
+            .transport(Flaky(count.clone()))
+            .retry(policy)
+            .retry_for::<Class>(RetryPolicy::none())
+            .build();
+
+        let res = context.get("https://a.test/").send().await.unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        assert_eq!(3, count.swap(0, Ordering::SeqCst));
+
+        // POST 默认不重放
+        let res = context.post("https://a.test/").send().await.unwrap();
+        assert_eq!(StatusCode::SERVICE_UNAVAILABLE, res.status());
+        assert_eq!(1, count.swap(0, Ordering::SeqCst));
+
+        // 显式标记幂等后重试
+        let res = context
+            .post("https://a.test/")
+            .idempotent(true)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(StatusCode::OK, res.status());
+        assert_eq!(3, count.swap(0, Ordering::SeqCst));
+
+        // 按组覆盖
+        let res = context.class().get("https://a.test/").send().await.unwrap();
+        assert_eq!(StatusCode::SERVICE_UNAVAILABLE, res.status());
+        assert_eq!(1, count.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_hot_swap() {
+        fn assert_send_sync<T: Send + Sync + 'static>() {}
+        assert_send_sync::<crate::Context>();
+
+        let context = Arc::new(ContextBuilder::new().transport(Fake).build());
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let context = context.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..1000 {
+                        // 快照在切换后仍然有效
+                        let cookies = context.get_cookies();
+                        let _ = cookies.get("a.test", "session").map(|c| c.raw().len());
+                    }
+                })
+            })
+            .collect();
+        for i in 0..1000 {
+            let mut cookies = crate::store::cookies::CookieStore::default();
+            let cookie = crate::store::cookies::Cookie::parse(&format!("session={i}"));
+            cookies.insert("a.test", cookie);
+            context.set_cookies(cookies);
+        }
+        for r in readers {
+            r.join().unwrap();
+        }
+        let cookies = context.get_cookies();
+        let cookie = cookies.get("a.test", "session").unwrap();
+        assert_eq!(Some("999"), cookie.value());
+    }
+
+    #[tokio::test]
+    async fn test_dns_failure() {
+        use crate::error::Code;
+
+        // .invalid 保证无法解析, 离线时同样是解析失败
+        let context = ContextBuilder::new().build();
+        let err = context
+            .get("https://buaa-api.invalid/")
+            .send()
+            .await
+            .unwrap_err();
+        assert!(matches!(err.code(), Some(Code::NetworkDnsFailure)));
+    }
+}
@@ -0,0 +1,31 @@
+// `tracing` feature 下的埋点. 各个公开方法上的 `tracing::instrument` 创建以 `组.方法` 命名的 span,
+// 并用 `fields(url, status, retries, elapsed_ms, relogin)` 预先声明字段, 这里只往当前 span 上记录
+// 一次调用发出多个请求时, 字段保留最后一个请求的值
+
+#[cfg(feature = "tracing")]
+pub(crate) use std::time::Instant;
+
+// 在 API 调用的 span 内调用, 没有声明对应字段的 span 会忽略记录
+#[cfg(feature = "tracing")]
+pub(crate) fn record_request(
+    url: &reqwest::Url,
+    res: &crate::Result<super::Response>,
+    retries: u32,
+    start: Instant,
+) {
+    let span = tracing::Span::current();
+    span.record("url", tracing::field::display(url));
+    if let Ok(res) = res {
+        span.record("status", res.status.as_u16());
+    }
+    span.record("retries", retries);
+    span.record("elapsed_ms", start.elapsed().as_millis() as u64);
+}
+
+// 记录被服务端拒绝后重新登录的 token
+pub(crate) fn record_relogin(token: &'static str) {
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("relogin", token);
+    #[cfg(not(feature = "tracing"))]
+    let _ = token;
+}
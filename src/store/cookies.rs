@@ -1,36 +1,40 @@
 //! Self-implemented CookieStore
 //!
 //! According to the RFC 6265 specification (https://datatracker.ietf.org/doc/html/rfc6265), we made the following simplifications:
-//! 1. Do not consider the Max-Age and Expires attributes; the persistence and expiration are handled by the CredentialStore.
-//! 2. Do not consider the HttpOnly attribute, because we are not in a scripting environment.
-//! 3. Do not consider the old-style Domain attribute like `.example.com`; only consider the standard Domain attribute: `example.com`.
-//! 4. Do not consider the SameSite attribute, since we have no cross-site attack risk.
-//! 5. Do not consider the special meaning of Cookie prefixes.
+//! 1. Do not consider the HttpOnly attribute, because we are not in a scripting environment.
+//! 2. The leading dot of old-style Domain attribute like `.example.com` is ignored.
+//! 3. Do not consider the SameSite attribute, since we have no cross-site attack risk.
+//! 4. Do not consider the special meaning of Cookie prefixes.
 //! Our implementation only covers the following:
 //! When storing:
 //! 1. Record the original Set-Cookie string.
 //! 2. Record the end position of the name=value part. We assume this field always exists and is valid.
 //! 3. Record the Path, Domain, and Secure attributes if they exist.
+//! 4. Record the expiry time from Max-Age (preferred) or Expires. An already expired cookie deletes the stored one.
+//! 5. Reject cookies whose Domain does not domain-match the host.
 //! When sending:
 //! 1. Filter cookies by Path (if not None).
-//! 2. Filter cookies by Domain (if not None; otherwise treat as HostOnly Cookie). We assume they span at most one subdomain level.
+//! 2. Filter cookies by Domain (if not None; otherwise treat as HostOnly Cookie), matching any level of parent domain.
 //! 3. Filter cookies by the Secure attribute.
+//! 4. Filter out expired cookies, use [`CookieStore::purge_expired`] to remove them.
 
 // 根据 RFC 6265 规范 (https://datatracker.ietf.org/doc/html/rfc6265), 我们做出如下简化:
-// 1. 不考虑 Max-Age 和 Expires 属性, 持久化储存有效期交由 CredentialStore 处理
-// 2. 不考虑 HttpOnly 属性, 因为我们不是脚本环境
-// 3. 不考虑旧版 Domain 属性: `.example.com`. 只考虑标准 Domain 属性: `example.com`
-// 4. 不考虑 SameSite 属性, 毕竟我们没有跨域攻击的风险
-// 5. 不考虑 Cookie 前缀特殊含义
+// 1. 不考虑 HttpOnly 属性, 因为我们不是脚本环境
+// 2. 旧版 Domain 属性 `.example.com` 的前导点直接忽略
+// 3. 不考虑 SameSite 属性, 毕竟我们没有跨域攻击的风险
+// 4. 不考虑 Cookie 前缀特殊含义
 // 我们的实现只针对以下内容:
 // 储存时:
 // 1. 记录原始 Set-Cookie 字符串
 // 2. 记录 name=value 部分的结束位置. 我们假定这一字段一定存在. 并且合法
 // 3. 记录 Path, Domain, Secure 属性, 如果存在
+// 4. 记录过期时间, Max-Age 优先于 Expires. 已经过期的 Cookie 会删除已储存的同名 Cookie
+// 5. Domain 与 host 不匹配的 Cookie 直接丢弃
 // 发送时:
 // 1. 通过 Path 过滤 Cookie (如果不为 None)
-// 2. 通过 Domain 过滤 Cookie (如果不为 None, 否则视为 HostOnly Cookie). 并且我们假定最多只跨一级域名
+// 2. 通过 Domain 过滤 Cookie (如果不为 None, 否则视为 HostOnly Cookie). 逐级匹配所有父域名
 // 3. 通过 Secure 属性过滤 Cookie
+// 4. 过滤已过期的 Cookie
 
 use reqwest::Url;
 use reqwest::header::HeaderValue;
@@ -43,10 +47,11 @@ use std::path::Path;
 
 use crate::cell::AtomicCell;
 use crate::error::{Error, Result};
+use crate::utils::time::{DateTime, Month};
 
 // 简易 Cookie 结构, 我们不关心额外信息, 能存能发即可
 /// Simple Cookie
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Cookie {
     raw: String,
     // 直接记录 name=value 部分的结束位置
@@ -55,6 +60,9 @@ pub struct Cookie {
     path: Option<String>,
     domain: Option<String>,
     secure: bool,
+    // 过期时间 (Unix 秒), None 为会话 Cookie. 旧版 cookies.json 没有这个字段
+    #[serde(default)]
+    expires: Option<u64>,
 }
 
 impl Cookie {
@@ -65,10 +73,13 @@ impl Cookie {
         let mut path = None;
         let mut domain = None;
         let mut secure = false;
+        let mut max_age = None;
+        let mut expires = None;
 
-        for part in raw.split(';') {
+        // 第一段是 name=value, 不能当作属性解析
+        for part in raw.split(';').skip(1) {
             if let Some((k, v)) = part.trim().split_once('=') {
-                match k.to_lowercase().as_str() {
+                match k.trim().to_lowercase().as_str() {
                     // 小于等于 1 时为 '/' 或 没有
                     "path" if v.len() > 1 => {
                         path = Some(v.trim().to_string());
@@ -76,8 +87,16 @@ impl Cookie {
                     "domain" => {
                         // 在这里移除可能的前导点, 以简化后续匹配
                         // 例如 LiveApi 就会产生这种域名
-                        let v = v.trim_start_matches('.');
-                        domain = Some(v.trim().to_string());
+                        let v = v.trim().trim_start_matches('.');
+                        if !v.is_empty() {
+                            domain = Some(v.to_lowercase());
+                        }
+                    }
+                    "max-age" => {
+                        max_age = v.trim().parse::<i64>().ok();
+                    }
+                    "expires" => {
+                        expires = parse_http_date(v);
                     }
                     _ => {}
                 }
@@ -89,12 +108,20 @@ impl Cookie {
             }
         }
 
+        // Max-Age 优先, 非正数表示立即过期
+        let expires = match max_age {
+            Some(age) if age <= 0 => Some(0),
+            Some(age) => Some(DateTime::secs().saturating_add(age as u64)),
+            None => expires.map(|t: i64| t.max(0) as u64),
+        };
+
         Cookie {
             raw: raw.to_string(),
             item: item_idx,
             path,
             domain,
             secure,
+            expires,
         }
     }
 
@@ -118,6 +145,16 @@ impl Cookie {
         self.item().split_once('=').map(|(_, value)| value)
     }
 
+    /// Get expiry time in seconds since UNIX_EPOCH, `None` for a session cookie
+    pub fn expires(&self) -> Option<u64> {
+        self.expires
+    }
+
+    /// Whether the cookie is expired
+    pub fn is_expired(&self) -> bool {
+        self.expires.is_some_and(|t| t <= DateTime::secs())
+    }
+
     fn path(&self) -> &str {
         self.path.as_deref().unwrap_or("/")
     }
@@ -126,17 +163,150 @@ impl Cookie {
         self.domain.as_deref()
     }
 
-    // 匹配路径与安全属性, 至于 host 已经在 get 方法中处理过了
+    // 匹配路径, 安全属性与有效期, 至于 host 已经在 get 方法中处理过了
     fn matches_url(&self, url: &Url) -> bool {
-        url.path().starts_with(self.path()) && (!self.secure || url.scheme() == "https")
+        url.path().starts_with(self.path())
+            && (!self.secure || url.scheme() == "https")
+            && !self.is_expired()
+    }
+
+    // 从导入格式的各个字段构造, 拼出等价的 Set-Cookie 字符串作为 raw
+    fn from_parts(
+        name: &str,
+        value: &str,
+        domain: Option<&str>,
+        path: &str,
+        secure: bool,
+        expires: Option<u64>,
+    ) -> Cookie {
+        let mut raw = format!("{name}={value}");
+        let item = raw.len();
+        let domain = domain
+            .map(|d| d.trim_start_matches('.').to_lowercase())
+            .filter(|d| !d.is_empty());
+        if let Some(domain) = &domain {
+            raw.push_str(&format!("; Domain={domain}"));
+        }
+        // 与 parse 一致, 根路径不记录
+        let path = (path.len() > 1).then(|| path.to_string());
+        if let Some(path) = &path {
+            raw.push_str(&format!("; Path={path}"));
+        }
+        if secure {
+            raw.push_str("; Secure");
+        }
+        Cookie {
+            raw,
+            item,
+            path,
+            domain,
+            secure,
+            expires,
+        }
+    }
+}
+
+// 浏览器扩展 (EditThisCookie, Cookie-Editor 等) 导出的 JSON 格式中的一项
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BrowserCookie {
+    domain: String,
+    #[serde(default)]
+    host_only: Option<bool>,
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    secure: bool,
+    #[serde(default)]
+    http_only: bool,
+    #[serde(default)]
+    session: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expiration_date: Option<f64>,
+    name: String,
+    value: String,
+}
+
+// host 本身及其各级父域名, 例如 a.b.c -> [a.b.c, b.c, c]
+fn domain_suffixes(host: &str) -> impl Iterator<Item = &str> {
+    std::iter::successors(Some(host), |h| h.split_once('.').map(|(_, rest)| rest))
+}
+
+// path 等于 prefix 或者位于其下一级以下, 已去掉末尾的 '/'
+fn path_within(path: &str, prefix: &str) -> bool {
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+// RFC 6265 5.3: host 等于 domain, 或者以 ".domain" 结尾且不是 IP
+fn domain_match(host: &str, domain: &str) -> bool {
+    host == domain
+        || (host.ends_with(domain)
+            && host.as_bytes()[host.len() - domain.len() - 1] == b'.'
+            && host.parse::<std::net::IpAddr>().is_err())
+}
+
+// RFC 6265 5.1.1 的宽松日期解析, 例如 `Wed, 21 Oct 2015 07:28:00 GMT` 或 `Wed, 21-Oct-15 07:28:00 GMT`
+// 返回 Unix 秒, 解析失败返回 None (此时视为会话 Cookie)
+fn parse_http_date(s: &str) -> Option<i64> {
+    let mut time = None;
+    let mut day = None;
+    let mut month = None;
+    let mut year = None;
+
+    let tokens = s
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == ':'))
+        .filter(|t| !t.is_empty());
+    for token in tokens {
+        if time.is_none() && token.contains(':') {
+            let mut parts = token.split(':').map(|p| p.parse::<u32>().ok());
+            if let (Some(Some(h)), Some(Some(m)), Some(Some(sec))) =
+                (parts.next(), parts.next(), parts.next())
+            {
+                time = Some((h, m, sec));
+                continue;
+            }
+        }
+        if day.is_none()
+            && (1..=2).contains(&token.len())
+            && let Ok(d) = token.parse::<u32>()
+        {
+            day = Some(d);
+            continue;
+        }
+        if month.is_none() && token.len() >= 3 {
+            let prefix = token[..3].to_lowercase();
+            const MONTHS: [&str; 12] = [
+                "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+            ];
+            if let Some(i) = MONTHS.iter().position(|m| *m == prefix) {
+                month = Month::from_num(i as u32 + 1);
+                continue;
+            }
+        }
+        if year.is_none()
+            && (2..=4).contains(&token.len())
+            && let Ok(y) = token.parse::<i32>()
+        {
+            year = Some(match y {
+                70..=99 => y + 1900,
+                0..=69 => y + 2000,
+                _ => y,
+            });
+        }
     }
+
+    let (h, m, sec) = time?;
+    let dt = DateTime::from_calendar(year?, month?, day?, h, m, sec).ok()?;
+    // from_calendar 按 UTC+8 解释, 而 HTTP 日期是 GMT
+    Some(dt.timestamp() + 8 * 3600)
 }
 
 type NameMap = HashMap<String, Cookie>;
 type DomainMap = HashMap<String, NameMap>;
 
 /// Simple Cookie Store
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CookieStore {
     store: DomainMap,
 }
@@ -194,6 +364,40 @@ impl CookieStore {
             .and_then(|name_map| name_map.get(name))
     }
 
+    /// Get a cookie that would be sent to the URL, by name
+    pub fn get_by_url(&self, url: &Url, name: &str) -> Option<&Cookie> {
+        let host = url.host_str()?;
+        // 与 get_request_values 相同, 由近及远逐级查找
+        domain_suffixes(host).find_map(|domain| {
+            self.store
+                .get(domain)
+                .and_then(|name_map| name_map.get(name))
+                .filter(|cookie| domain == host || cookie.domain().is_some())
+                .filter(|cookie| !cookie.is_expired())
+        })
+    }
+
+    /// Get a cookie of the service mounted at `base`, by name
+    ///
+    /// Unlike [`CookieStore::get_by_url`], cookies scoped to a sub path of `base` are included.
+    /// Under WebVPN every service shares the portal host and is told apart by the path of `base`.
+    pub fn get_by_base(&self, base: &Url, name: &str) -> Option<&Cookie> {
+        let host = base.host_str()?;
+        let base_path = base.path().trim_end_matches('/');
+        domain_suffixes(host).find_map(|domain| {
+            self.store
+                .get(domain)
+                .and_then(|name_map| name_map.get(name))
+                .filter(|cookie| domain == host || cookie.domain().is_some())
+                // 按路径段比较, 路径是 base 的上级或者下级都算
+                .filter(|cookie| {
+                    let path = cookie.path().trim_end_matches('/');
+                    path_within(path, base_path) || path_within(base_path, path)
+                })
+                .filter(|cookie| !cookie.is_expired())
+        })
+    }
+
     /// Insert a cookie
     pub fn insert(&mut self, domain: &str, cookie: Cookie) -> Option<Cookie> {
         let name = cookie.name()?;
@@ -212,6 +416,20 @@ impl CookieStore {
             .and_then(|name_map| name_map.remove(name))
     }
 
+    /// Remove cookies of the host of `url` whose path is under the path of `url`, return the number removed
+    ///
+    /// Cookies set for a parent domain are shared with other hosts, so they are kept
+    pub fn remove_by_url(&mut self, url: &Url) -> usize {
+        let Some(name_map) = url.host_str().and_then(|host| self.store.get_mut(host)) else {
+            return 0;
+        };
+        let before = name_map.len();
+        name_map.retain(|_, cookie| !cookie.path().starts_with(url.path()));
+        let removed = before - name_map.len();
+        self.store.retain(|_, name_map| !name_map.is_empty());
+        removed
+    }
+
     /// Store response cookies
     pub fn store_response_cookies<I>(&mut self, cookies: I, url: &Url)
     where
@@ -223,13 +441,27 @@ impl CookieStore {
                     log::debug!("Skipping cookie with empty name: {}", cookie.raw());
                     continue;
                 }
-                if let Some(domain) = cookie.domain().or(url.host_str()) {
-                    let name_map = self
-                        .store
-                        .entry(domain.to_string())
-                        .or_insert_with(HashMap::new);
-                    name_map.insert(name.to_string(), cookie);
+                let Some(host) = url.host_str() else {
+                    continue;
+                };
+                let domain = match cookie.domain() {
+                    Some(domain) if !domain_match(host, domain) => {
+                        log::debug!("Skipping cookie for foreign domain: {}", cookie.raw());
+                        continue;
+                    }
+                    Some(domain) => domain,
+                    None => host,
+                };
+                // 服务端用过期的 Cookie 删除同名 Cookie
+                if cookie.is_expired() {
+                    self.remove(domain, name);
+                    continue;
                 }
+                let name_map = self
+                    .store
+                    .entry(domain.to_string())
+                    .or_insert_with(HashMap::new);
+                name_map.insert(name.to_string(), cookie);
             } else {
                 log::debug!("Skipping bad cookie (no 'name=value'): {}", cookie.raw());
             }
@@ -241,32 +473,142 @@ impl CookieStore {
         // 如果解包 host 不合法, 使用空字符串也能返回空迭代器
         let host = url.host_str().unwrap_or("");
 
-        // HostOnly Cookies 迭代器
-        let exact_iter = self
+        // host 自身储存 HostOnly 和 Domain Cookie, 各级父域名只带上设置了 Domain 的
+        domain_suffixes(host)
+            .filter_map(|domain| self.store.get(domain).map(|name_map| (domain, name_map)))
+            .flat_map(move |(domain, name_map)| {
+                name_map
+                    .values()
+                    .filter(move |cookie| domain == host || cookie.domain().is_some())
+            })
+            .filter(move |cookie| cookie.matches_url(url))
+            .map(|cookie| cookie.item())
+    }
+
+    /// Remove all expired cookies, return the number removed
+    pub fn purge_expired(&mut self) -> usize {
+        let mut removed = 0;
+        for name_map in self.store.values_mut() {
+            let before = name_map.len();
+            name_map.retain(|_, cookie| !cookie.is_expired());
+            removed += before - name_map.len();
+        }
+        self.store.retain(|_, name_map| !name_map.is_empty());
+        removed
+    }
+
+    // 按域名和名称排序, 导出结果稳定
+    fn sorted(&self) -> Vec<(&str, &Cookie)> {
+        let mut cookies: Vec<_> = self
             .store
-            .get(host)
+            .iter()
+            .flat_map(|(key, name_map)| name_map.values().map(move |c| (key.as_str(), c)))
+            .collect();
+        cookies.sort_by(|a, b| (a.0, a.1.name()).cmp(&(b.0, b.1.name())));
+        cookies
+    }
+
+    /// Import from Netscape `cookies.txt` format, used by curl, wget and yt-dlp
+    ///
+    /// Each line is `domain  include_subdomains  path  secure  expires  name  value`, separated by tabs.
+    /// Comment lines are ignored, except the `#HttpOnly_` prefix. Expiry `0` means a session cookie
+    pub fn from_netscape(s: &str) -> Result<Self> {
+        let mut store = Self::default();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [domain, subdomains, path, secure, expires, name, value] = fields[..] else {
+                return Err(Error::parse("Invalid Netscape cookie line")
+                    .with_source(format!("Line {}: {line}", i + 1)));
+            };
+            let expires = expires.parse::<u64>().map_err(|e| {
+                Error::parse("Invalid Netscape cookie expiry")
+                    .with_source(format!("Line {}: {e}", i + 1))
+            })?;
+            let host = domain.trim_start_matches('.').to_lowercase();
+            let domain = subdomains
+                .eq_ignore_ascii_case("TRUE")
+                .then_some(host.as_str());
+            let expires = (expires != 0).then_some(expires);
+            let secure = secure.eq_ignore_ascii_case("TRUE");
+            let cookie = Cookie::from_parts(name, value, domain, path, secure, expires);
+            store.insert(&host, cookie);
+        }
+        Ok(store)
+    }
+
+    /// Export to Netscape `cookies.txt` format, see [`CookieStore::from_netscape`]
+    pub fn to_netscape(&self) -> String {
+        let mut out = String::from("# Netscape HTTP Cookie File\n");
+        for (key, cookie) in self.sorted() {
+            let (Some(name), Some(value)) = (cookie.name(), cookie.value()) else {
+                continue;
+            };
+            let (domain, subdomains) = match cookie.domain() {
+                Some(domain) => (format!(".{domain}"), "TRUE"),
+                None => (key.to_string(), "FALSE"),
+            };
+            let secure = if cookie.secure { "TRUE" } else { "FALSE" };
+            let expires = cookie.expires.unwrap_or(0);
+            let path = cookie.path();
+            out.push_str(&format!(
+                "{domain}\t{subdomains}\t{path}\t{secure}\t{expires}\t{name}\t{value}\n"
+            ));
+        }
+        out
+    }
+
+    /// Import from the JSON array exported by browser extensions like EditThisCookie and Cookie-Editor
+    ///
+    /// Fields used: `domain`, `hostOnly`, `path`, `secure`, `session`, `expirationDate`, `name` and `value`
+    pub fn from_browser_json(s: &str) -> Result<Self> {
+        let cookies: Vec<BrowserCookie> = serde_json::from_str(s)
+            .map_err(|e| Error::parse("Invalid browser cookie JSON").with_source(e))?;
+        let mut store = Self::default();
+        for c in cookies {
+            let host = c.domain.trim_start_matches('.').to_lowercase();
+            // 没有 hostOnly 字段时, 以前导点判断是否跨子域名
+            let host_only = c.host_only.unwrap_or(!c.domain.starts_with('.'));
+            let domain = (!host_only).then_some(host.as_str());
+            let expires = match c.expiration_date {
+                Some(t) if !c.session => Some(t.max(0.0) as u64),
+                _ => None,
+            };
+            let path = c.path.as_deref().unwrap_or("/");
+            let cookie = Cookie::from_parts(&c.name, &c.value, domain, path, c.secure, expires);
+            store.insert(&host, cookie);
+        }
+        Ok(store)
+    }
+
+    /// Export to the browser extension JSON format, see [`CookieStore::from_browser_json`]
+    pub fn to_browser_json(&self) -> Result<String> {
+        let cookies: Vec<BrowserCookie> = self
+            .sorted()
             .into_iter()
-            .flat_map(|name_map| name_map.values())
-            .filter(move |cookie| cookie.matches_url(url))
-            .map(|cookie| cookie.item());
-
-        // Suffix Domain Cookies 迭代器
-        // 看看有没有需要带上的父域名 cookie
-        // 不过考虑到实际应用我们只处理一级父域名即可
-        let parent_iter = host
-            .find('.')
-            .and_then(|dot_pos| {
-                let parent = &host[dot_pos + 1..];
-                self.store.get(parent)
+            .filter_map(|(key, cookie)| {
+                Some(BrowserCookie {
+                    domain: match cookie.domain() {
+                        Some(domain) => format!(".{domain}"),
+                        None => key.to_string(),
+                    },
+                    host_only: Some(cookie.domain().is_none()),
+                    path: Some(cookie.path().to_string()),
+                    secure: cookie.secure,
+                    http_only: false,
+                    session: cookie.expires.is_none(),
+                    expiration_date: cookie.expires.map(|t| t as f64),
+                    name: cookie.name()?.to_string(),
+                    value: cookie.value()?.to_string(),
+                })
             })
-            .into_iter()
-            .flat_map(|name_map| name_map.values())
-            // 如果确实设置了 domain 则尝试匹配
-            .filter(move |cookie| cookie.domain().is_some() && cookie.matches_url(url))
-            .map(|cookie| cookie.item());
-
-        // 合并两个迭代器
-        exact_iter.chain(parent_iter)
+            .collect();
+        serde_json::to_string_pretty(&cookies)
+            .map_err(|e| Error::parse("Failed to serialize cookies").with_source(e))
     }
 }
 
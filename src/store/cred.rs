@@ -4,14 +4,23 @@ use serde::{Deserialize, Serialize};
 
 use std::fs::OpenOptions;
 use std::path::Path;
-use std::sync::atomic::{AtomicU64, Ordering};
 
 use crate::api::{Aas, App, Boya, Class, Cloud, Live, Spoc, Srs, Sso, Tes};
+use crate::crypto::aes::Aes128;
+use crate::crypto::sha1::{HmacSha1, pbkdf2_hmac_sha1};
 use crate::error::{Code, Error, Result};
 use crate::utils::time::DateTime;
 
+// 加密格式: MAGIC | 版本 (1) | 迭代次数 (4, 大端) | salt (16) | iv (16) | 密文 | HMAC (20)
+// 口令经 PBKDF2-HMAC-SHA1 派生出 AES-128 密钥和 HMAC 密钥, 先加密后认证
+const MAGIC: &[u8; 8] = b"BUAACRED";
+const VERSION: u8 = 1;
+const ITERATIONS: u32 = 100_000;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 4 + 16 + 16;
+const TAG_LEN: usize = 20;
+
 /// Store for credentials
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct CredentialStore {
     pub(crate) username: Option<String>,
     pub(crate) password: Option<String>,
@@ -44,8 +53,14 @@ pub(crate) trait Token {
     fn mut_field(store: &mut CredentialStore) -> &mut CredentialItem;
 }
 
+// 服务的 Cookie 范围: 主机字段名, 可选附加路径, 例如 `spoc + "/pjxt"`
+// 可选的 expire 列表: 清 Cookie 时一并失效的其他 token, 它们的会话 Cookie 也在这些范围内
 macro_rules! impl_token {
-    ($type:ident, $field:ident, $expiration:expr) => {
+    (
+        $type:ident, $field:ident, $expiration:expr,
+        [$($host:ident $(+ $path:literal)?),+]
+        $(, expire [$($shared:ident),+])?
+    ) => {
         impl Token for $type {
             const NAME: &'static str = stringify!($type);
             const EXPIRATION: u64 = $expiration;
@@ -58,31 +73,82 @@ macro_rules! impl_token {
                 &mut store.$field
             }
         }
+
+        impl crate::Context<$type> {
+            #[doc = concat!("Reset ", stringify!($type), " session, the next request logs in again")]
+            ///
+            /// Clears its credential and the cookies of its service.
+            /// Groups whose session cookies are cleared with them log in again on their next request,
+            /// e.g. Tes for Spoc
+            pub fn reset(&self) {
+                self.reset_token::<$type>(&[$(
+                    format!(concat!("{}" $(, $path)?), self.endpoints.$host)
+                ),+]);
+                $(self.cred.update(|c| {
+                    $(c.expire::<$shared>();)+
+                });)?
+            }
+        }
     };
 }
 
 // 我们这里做保守估计防止 token 意外失效
 
 // 测得 3 小时仍有效
-impl_token!(Aas, aas, 10800);
+impl_token!(Aas, aas, 10800, [byxt]);
 // 理论上一年内有效, 但 24 小时就够用了
-impl_token!(App, app, 86400);
+impl_token!(App, app, 86400, [app]);
 // 测得 15 分钟以内有效, 这里用 10 分钟. 使用可刷新时效
-impl_token!(Boya, boya_token, 600);
+impl_token!(Boya, boya_token, 600, [bykc]);
 // 测得 7 天以内有效, 但 24 小时就够用了
-impl_token!(Class, class_token, 86400);
+impl_token!(Class, class_token, 86400, [iclass, iclass_app]);
 // 测得 40 分钟以内有效, 但某些操作会快速过期, 防止意外这里用 10 分钟. 使用可刷新时效
-impl_token!(Cloud, cloud_token, 600);
+impl_token!(Cloud, cloud_token, 600, [bhpan]);
 // TODO: Cookie 标记有效期 16 小时, 这里暂定 12 小时. 使用不可刷新时效
-impl_token!(Live, live_token, 43200);
+impl_token!(Live, live_token, 43200, [yjapi, classroom]);
 // 测得 5 小时以内有效, 这里用 3 小时. 使用不可刷新时效
-impl_token!(Spoc, spoc_token, 10800);
+// Tes 的会话 Cookie 也在 Spoc 主机下
+impl_token!(Spoc, spoc_token, 10800, [spoc], expire[Tes]);
 // 测得 25 分钟以内有效, 这里用 20 分钟. 使用不可刷新时效
-impl_token!(Srs, srs_token, 1200);
+impl_token!(Srs, srs_token, 1200, [byxk]);
 // 测得 90 分钟以内有效. 使用可刷新时效
-impl_token!(Sso, sso, 5400);
+impl_token!(Sso, sso, 5400, [sso]);
 // 测得 60 分钟以内有效
-impl_token!(Tes, tes, 3600);
+// 与 Spoc 共用主机, 只清理自己路径下的 Cookie
+impl_token!(Tes, tes, 3600, [spoc + "/pjxt"]);
+
+// 由口令和盐派生的密钥. 派生要跑 ITERATIONS 轮 PBKDF2, FileStorage 缓存它,
+// 自动保存时只换 IV 不换盐, 不必每次重新派生
+pub(crate) struct CredentialKey {
+    salt: [u8; 16],
+    aes: Aes128,
+    hmac: HmacSha1,
+}
+
+impl CredentialKey {
+    // 使用随机盐
+    pub(crate) fn new(passphrase: &str) -> Result<Self> {
+        let mut salt = [0u8; 16];
+        random(&mut salt)?;
+        Ok(Self::derive(passphrase, salt))
+    }
+
+    // 前 16 字节作 AES 密钥, 后 20 字节作 HMAC 密钥
+    fn derive(passphrase: &str, salt: [u8; 16]) -> Self {
+        let mut key = [0u8; 36];
+        pbkdf2_hmac_sha1(passphrase.as_bytes(), &salt, ITERATIONS, &mut key);
+        CredentialKey {
+            salt,
+            aes: Aes128::new(&key[..16]),
+            hmac: HmacSha1::new(&key[16..]),
+        }
+    }
+}
+
+// 盐和 IV 取自操作系统的随机源
+fn random(buf: &mut [u8]) -> Result<()> {
+    getrandom::getrandom(buf).map_err(|e| Error::io("Failed to get random bytes").with_source(e))
+}
 
 impl CredentialStore {
     /// Load credential store from file, if file not exist or invalid, return default store
@@ -107,6 +173,111 @@ impl CredentialStore {
             .map_err(|e| Error::io("Failed to write cred.json").with_source(e))
     }
 
+    /// Load credential store from a file written by [`CredentialStore::to_file_encrypted`]
+    pub fn from_file_encrypted<P: AsRef<Path>>(path: P, passphrase: &str) -> Result<Self> {
+        Self::read_encrypted(path, passphrase).map(|(cred, _)| cred)
+    }
+
+    /// Save credential store to file, encrypted with `passphrase`
+    pub fn to_file_encrypted<P: AsRef<Path>>(&self, path: P, passphrase: &str) -> Result<()> {
+        self.write_encrypted(path, &CredentialKey::new(passphrase)?)
+    }
+
+    // 同时返回派生出的密钥, 供之后保存时复用
+    pub(crate) fn read_encrypted<P: AsRef<Path>>(
+        path: P,
+        passphrase: &str,
+    ) -> Result<(Self, CredentialKey)> {
+        let data = std::fs::read(path)
+            .map_err(|e| Error::io("Failed to open encrypted credentials").with_source(e))?;
+        Self::decrypt(&data, passphrase)
+    }
+
+    pub(crate) fn write_encrypted<P: AsRef<Path>>(
+        &self,
+        path: P,
+        key: &CredentialKey,
+    ) -> Result<()> {
+        std::fs::write(path, self.encrypt(key)?)
+            .map_err(|e| Error::io("Failed to write encrypted credentials").with_source(e))
+    }
+
+    /// Decrypt credential store from bytes produced by [`CredentialStore::to_encrypted`]
+    ///
+    /// A wrong passphrase and tampered data are both reported as an auth error
+    pub fn from_encrypted(data: &[u8], passphrase: &str) -> Result<Self> {
+        Self::decrypt(data, passphrase).map(|(cred, _)| cred)
+    }
+
+    /// Encrypt credential store with `passphrase`
+    ///
+    /// The format is versioned, with a random salt and IV for every call
+    pub fn to_encrypted(&self, passphrase: &str) -> Result<Vec<u8>> {
+        self.encrypt(&CredentialKey::new(passphrase)?)
+    }
+
+    pub(crate) fn decrypt(data: &[u8], passphrase: &str) -> Result<(Self, CredentialKey)> {
+        if data.len() < HEADER_LEN + TAG_LEN || &data[..MAGIC.len()] != MAGIC {
+            return Err(Error::parse("Not encrypted credentials"));
+        }
+        let version = data[MAGIC.len()];
+        if version != VERSION {
+            return Err(Error::parse("Unsupported encrypted credentials version")
+                .with_source(format!("Version: {version}")));
+        }
+        let mut pos = MAGIC.len() + 1;
+        let mut iterations = [0u8; 4];
+        iterations.copy_from_slice(&data[pos..pos + 4]);
+        let iterations = u32::from_be_bytes(iterations);
+        // 轮数来自文件, 不可信, 只接受当前版本的固定值, 避免被构造的文件拖住
+        if iterations != ITERATIONS {
+            return Err(Error::parse("Unsupported encrypted credentials iterations")
+                .with_source(format!("Iterations: {iterations}")));
+        }
+        pos += 4;
+        let mut salt = [0u8; 16];
+        salt.copy_from_slice(&data[pos..pos + 16]);
+        let mut iv = [0u8; 16];
+        iv.copy_from_slice(&data[pos + 16..pos + 32]);
+
+        let (body, tag) = data.split_at(data.len() - TAG_LEN);
+        let key = CredentialKey::derive(passphrase, salt);
+        // 逐字节比较全部内容, 避免提前退出泄露信息
+        let expected = key.hmac.compute(body);
+        if expected
+            .iter()
+            .zip(tag)
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            != 0
+        {
+            return Err(Error::auth("Wrong passphrase or corrupted credentials"));
+        }
+
+        let plain = key.aes.decrypt_cfb(&body[HEADER_LEN..], &iv);
+        let cred = serde_json::from_slice(&plain)
+            .map_err(|e| Error::parse("Failed to read encrypted credentials").with_source(e))?;
+        Ok((cred, key))
+    }
+
+    // 每次加密都换新的 IV, 盐随密钥不变
+    pub(crate) fn encrypt(&self, key: &CredentialKey) -> Result<Vec<u8>> {
+        let plain = serde_json::to_vec(self)
+            .map_err(|e| Error::parse("Failed to serialize credentials").with_source(e))?;
+        let mut iv = [0u8; 16];
+        random(&mut iv)?;
+
+        let mut data = Vec::with_capacity(HEADER_LEN + plain.len() + TAG_LEN);
+        data.extend_from_slice(MAGIC);
+        data.push(VERSION);
+        data.extend_from_slice(&ITERATIONS.to_be_bytes());
+        data.extend_from_slice(&key.salt);
+        data.extend_from_slice(&iv);
+        data.extend_from_slice(&key.aes.encrypt_cfb(&plain, &iv));
+        let tag = key.hmac.compute(&data);
+        data.extend_from_slice(&tag);
+        Ok(data)
+    }
+
     pub(crate) fn username(&self) -> Result<&str> {
         self.username
             .as_deref()
@@ -129,34 +300,47 @@ impl CredentialStore {
     }
 
     pub(crate) fn is_expired<T: Token>(&self) -> bool {
-        T::field(self).expiration.load(Ordering::Relaxed) < DateTime::secs()
+        T::field(self).expiration < DateTime::secs()
+    }
+
+    // 服务端已经拒绝了凭据, 本地时效不再可信
+    // 和 refresh 一样只能经由 AtomicCell::update 调用
+    // 否则并发的 update 会复制出新值, 写在旧快照上的改动随之丢失
+    pub(crate) fn expire<T: Token>(&mut self) {
+        T::mut_field(self).expiration = 0;
     }
 
-    // 原子类型可以直接在不可变引用上更新
     // 如果调用了 Update 就不需要这个方法了
-    pub(crate) fn refresh<T: Token>(&self) {
-        T::field(self)
-            .expiration
-            .store(DateTime::secs() + T::EXPIRATION, Ordering::Relaxed);
+    pub(crate) fn refresh<T: Token>(&mut self) {
+        T::mut_field(self).expiration = DateTime::secs() + T::EXPIRATION;
     }
 
     // Update 动作包含 Refresh
     pub(crate) fn update<T: Token>(&mut self, value: String) {
         let now = DateTime::secs();
         let item = T::mut_field(self);
-        item.expiration
-            .store(now + T::EXPIRATION, Ordering::Relaxed);
+        item.expiration = now + T::EXPIRATION;
         item.value = Some(value);
         // 能用上 set 方法一定伴随着 Sso 的刷新
         // 而且 Sso 一定不会用上 set 方法
         // 所以这里可以放心的做一次 Sso 的刷新
-        self.sso.expiration.store(now + 5400, Ordering::Relaxed);
+        self.sso.expiration = now + 5400;
     }
 }
 
 /// Credential item
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct CredentialItem {
     value: Option<String>,
-    expiration: AtomicU64,
+    expiration: u64,
+}
+
+impl CredentialItem {
+    pub(crate) fn has_value(&self) -> bool {
+        self.value.is_some()
+    }
+
+    pub(crate) fn expiration(&self) -> u64 {
+        self.expiration
+    }
 }
@@ -2,3 +2,6 @@
 
 pub mod cookies;
 pub mod cred;
+pub mod session;
+pub mod storage;
+mod test;
@@ -0,0 +1,145 @@
+//! Session status of every API group
+
+use std::time::Duration;
+
+use crate::Context;
+use crate::api::{Aas, App, Boya, Class, Cloud, Live, Spoc, Srs, Sso, Tes};
+use crate::error::Error;
+use crate::utils::time::{DateTime, Week};
+
+use super::cred::{CredentialStore, Token};
+
+/// Status of all API group credentials, see [`Context::session_report`]
+#[derive(Debug)]
+pub struct SessionReport {
+    /// Username of the account, if set
+    pub username: Option<String>,
+    /// One entry per API group with a token, in the order
+    /// Aas, App, Boya, Class, Cloud, Live, Spoc, Srs, Sso, Tes
+    pub tokens: Vec<TokenStatus>,
+}
+
+/// Status of a single API group credential
+#[derive(Debug)]
+pub struct TokenStatus {
+    /// Name of the API group, e.g. `Boya`
+    pub name: &'static str,
+    /// Whether a token value is stored
+    ///
+    /// Aas, App, Sso and Tes keep their session in cookies, so they never have one
+    pub has_value: bool,
+    /// Local expiration time, `None` if never logged in
+    pub expires_at: Option<DateTime>,
+    /// Remaining lifetime, zero once expired
+    pub remaining: Duration,
+    /// Result of [`SessionReport::verify`], `None` if not probed
+    pub verified: Option<Probe>,
+}
+
+/// Result of probing a service with the stored session
+#[derive(Debug)]
+pub enum Probe {
+    /// The server accepted the stored session
+    Valid,
+    /// The server rejected the stored session, and a new login succeeded
+    Renewed,
+    /// The server rejected the stored session, no login was attempted
+    Rejected,
+    /// The probe failed, e.g. network error or login failure
+    Failed(Error),
+}
+
+impl TokenStatus {
+    fn new<T: Token>(cred: &CredentialStore) -> Self {
+        let item = T::field(cred);
+        let expiration = item.expiration();
+        let now = DateTime::secs();
+        TokenStatus {
+            name: T::NAME,
+            has_value: item.has_value(),
+            expires_at: (expiration != 0).then(|| DateTime::from_timestamp(expiration as i64)),
+            remaining: Duration::from_secs(expiration.saturating_sub(now)),
+            verified: None,
+        }
+    }
+
+    /// Whether the credential is still valid locally, the server may think otherwise
+    pub fn is_valid(&self) -> bool {
+        !self.remaining.is_zero()
+    }
+}
+
+impl SessionReport {
+    pub(crate) fn new(cred: &CredentialStore) -> Self {
+        SessionReport {
+            username: cred.username.clone(),
+            tokens: vec![
+                TokenStatus::new::<Aas>(cred),
+                TokenStatus::new::<App>(cred),
+                TokenStatus::new::<Boya>(cred),
+                TokenStatus::new::<Class>(cred),
+                TokenStatus::new::<Cloud>(cred),
+                TokenStatus::new::<Live>(cred),
+                TokenStatus::new::<Spoc>(cred),
+                TokenStatus::new::<Srs>(cred),
+                TokenStatus::new::<Sso>(cred),
+                TokenStatus::new::<Tes>(cred),
+            ],
+        }
+    }
+
+    /// Get status of an API group by name, e.g. `Boya`
+    pub fn get(&self, name: &str) -> Option<&TokenStatus> {
+        self.tokens.iter().find(|t| t.name == name)
+    }
+
+    /// Probe every locally valid session with a cheap read-only request
+    ///
+    /// Groups whose local credential already expired are skipped, they would only log in again.
+    /// A rejected session is renewed by the normal relogin, except SSO which is only checked
+    pub async fn verify(&mut self, context: &Context) {
+        for status in self.tokens.iter_mut().filter(|t| t.is_valid()) {
+            status.verified = Some(probe(context, status.name).await);
+        }
+    }
+}
+
+// 各组挑一个只读且参数最少的接口, 通过登录代数判断期间是否重新登录过
+async fn probe(context: &Context, name: &'static str) -> Probe {
+    let generation = context.flights.generation(name);
+    let res = match name {
+        "Aas" => context.aas().get_config().await.map(drop),
+        "App" => context.app().get_exam().await.map(drop),
+        "Boya" => context.boya().get_semester().await.map(drop),
+        "Class" => context
+            .class()
+            .query_schedule(&DateTime::now())
+            .await
+            .map(drop),
+        "Cloud" => context.cloud().get_user_dir().await.map(drop),
+        "Live" => {
+            let week = Week::current();
+            context.live().get_week_schedule(&week).await.map(drop)
+        }
+        "Spoc" => context.spoc().get_week().await.map(drop),
+        // get_batch 不带 token, 换用需要 token 的接口
+        "Srs" => context.srs().get_config().await.map(drop),
+        "Tes" => context.tes().get_task().await.map(drop),
+        // SSO 登录需要提交密码, 只检查 TGC 是否有效
+        _ => {
+            return match context.sso().probe().await {
+                Ok(true) => Probe::Valid,
+                Ok(false) => {
+                    context.cred.update(|c| c.expire::<Sso>());
+                    Probe::Rejected
+                }
+                Err(e) => Probe::Failed(e),
+            };
+        }
+    };
+    match res {
+        Ok(()) if context.flights.generation(name) == generation => Probe::Valid,
+        Ok(()) => Probe::Renewed,
+        Err(e) => Probe::Failed(e),
+    }
+}
@@ -0,0 +1,218 @@
+//! Pluggable persistence of cookies and credentials
+
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+use std::fs::OpenOptions;
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::error::{Error, Result};
+
+use super::cookies::CookieStore;
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+use super::cred::CredentialKey;
+use super::cred::CredentialStore;
+
+/// Backend to load and save authentication data (cookies and credentials)
+///
+/// Implement it to keep sessions in a database, a keyring or browser storage,
+/// then pass it to [`crate::ContextBuilder::storage`].
+///
+/// Built-in implementations:
+/// - [`FileStorage`]: `cookies.json` and `cred.json` in a directory
+/// - [`BundleStorage`]: both in a single JSON file
+/// - [`MemoryStorage`]: in memory, mainly for tests
+pub trait AuthStorage: Send + Sync + 'static {
+    /// Load cookies and credentials
+    fn load(&self) -> Result<(CookieStore, CredentialStore)>;
+
+    /// Save cookies and credentials
+    fn save(&self, cookies: &CookieStore, cred: &CredentialStore) -> Result<()>;
+}
+
+impl<S: AuthStorage + ?Sized> AuthStorage for Arc<S> {
+    fn load(&self) -> Result<(CookieStore, CredentialStore)> {
+        (**self).load()
+    }
+
+    fn save(&self, cookies: &CookieStore, cred: &CredentialStore) -> Result<()> {
+        (**self).save(cookies, cred)
+    }
+}
+
+/// Save to `dir/cookies.json` and `dir/cred.json`, the layout of [`crate::Context::save_auth`]
+///
+/// With [`FileStorage::passphrase`], credentials are encrypted to `dir/cred.enc` instead,
+/// see [`CredentialStore::to_file_encrypted`]. The key derived from the passphrase is cached,
+/// so only the first load or save pays for the key derivation
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+#[derive(Clone)]
+pub struct FileStorage {
+    dir: PathBuf,
+    passphrase: Option<String>,
+    key: Arc<Mutex<Option<CredentialKey>>>,
+}
+
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+impl std::fmt::Debug for FileStorage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileStorage")
+            .field("dir", &self.dir)
+            .field("encrypted", &self.passphrase.is_some())
+            .finish()
+    }
+}
+
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+impl FileStorage {
+    /// Create a file storage in `dir`, the directory is not created
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        FileStorage {
+            dir: dir.as_ref().to_path_buf(),
+            passphrase: None,
+            key: Arc::default(),
+        }
+    }
+
+    /// Encrypt credentials with `passphrase`, cookies are still plaintext
+    pub fn passphrase(mut self, passphrase: &str) -> Self {
+        self.passphrase = Some(passphrase.to_string());
+        self.key = Arc::default();
+        self
+    }
+
+    fn key(&self) -> std::sync::MutexGuard<'_, Option<CredentialKey>> {
+        self.key.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+impl AuthStorage for FileStorage {
+    fn load(&self) -> Result<(CookieStore, CredentialStore)> {
+        let cookies = CookieStore::from_file(self.dir.join("cookies.json"))?;
+        let cred = match &self.passphrase {
+            Some(p) => {
+                let (cred, key) = CredentialStore::read_encrypted(self.dir.join("cred.enc"), p)?;
+                *self.key() = Some(key);
+                cred
+            }
+            None => CredentialStore::from_file(self.dir.join("cred.json"))?,
+        };
+        Ok((cookies, cred))
+    }
+
+    fn save(&self, cookies: &CookieStore, cred: &CredentialStore) -> Result<()> {
+        cookies.to_file(self.dir.join("cookies.json"))?;
+        match &self.passphrase {
+            Some(p) => {
+                // 首次保存才派生密钥, 之后复用
+                let mut key = self.key();
+                let k = match key.take() {
+                    Some(k) => k,
+                    None => CredentialKey::new(p)?,
+                };
+                let res = cred.write_encrypted(self.dir.join("cred.enc"), &k);
+                *key = Some(k);
+                res
+            }
+            None => cred.to_file(self.dir.join("cred.json")),
+        }
+    }
+}
+
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+#[derive(Serialize)]
+struct BundleRef<'a> {
+    cookies: &'a CookieStore,
+    cred: &'a CredentialStore,
+}
+
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+#[derive(Deserialize)]
+struct Bundle {
+    cookies: CookieStore,
+    cred: CredentialStore,
+}
+
+/// Save cookies and credentials together in a single JSON file
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+#[derive(Debug, Clone)]
+pub struct BundleStorage {
+    path: PathBuf,
+}
+
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+impl BundleStorage {
+    /// Create a bundle storage at file `path`
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        BundleStorage {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+#[cfg(not(any(target_arch = "wasm32", target_arch = "wasm64")))]
+impl AuthStorage for BundleStorage {
+    fn load(&self) -> Result<(CookieStore, CredentialStore)> {
+        let file = OpenOptions::new()
+            .read(true)
+            .open(&self.path)
+            .map_err(|e| Error::io("Failed to open auth bundle").with_source(e))?;
+        let bundle: Bundle = serde_json::from_reader(file)
+            .map_err(|e| Error::parse("Failed to read auth bundle").with_source(e))?;
+        Ok((bundle.cookies, bundle.cred))
+    }
+
+    fn save(&self, cookies: &CookieStore, cred: &CredentialStore) -> Result<()> {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&self.path)
+            .map_err(|e| Error::io("Failed to open auth bundle").with_source(e))?;
+        serde_json::to_writer(file, &BundleRef { cookies, cred })
+            .map_err(|e| Error::io("Failed to write auth bundle").with_source(e))
+    }
+}
+
+/// Keep cookies and credentials in memory
+///
+/// Loading before anything is saved returns an error, like a missing file
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    data: Mutex<Option<(CookieStore, CredentialStore)>>,
+}
+
+impl MemoryStorage {
+    /// Create an empty memory storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether anything has been saved
+    pub fn is_empty(&self) -> bool {
+        self.data
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_none()
+    }
+}
+
+impl AuthStorage for MemoryStorage {
+    fn load(&self) -> Result<(CookieStore, CredentialStore)> {
+        self.data
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+            .ok_or_else(|| Error::io("Nothing saved in memory storage"))
+    }
+
+    fn save(&self, cookies: &CookieStore, cred: &CredentialStore) -> Result<()> {
+        let mut data = self.data.lock().unwrap_or_else(|e| e.into_inner());
+        *data = Some((cookies.clone(), cred.clone()));
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+#[cfg(test)]
+mod tests {
+    use reqwest::Url;
+
+    use crate::store::cookies::{Cookie, CookieStore};
+    use crate::store::cred::CredentialStore;
+
+    fn request(store: &CookieStore, url: &str) -> Vec<String> {
+        let url = Url::parse(url).unwrap();
+        let mut values: Vec<String> = store.get_request_values(&url).map(String::from).collect();
+        values.sort();
+        values
+    }
+
+    #[test]
+    fn test_cookie_domain() {
+        let mut store = CookieStore::default();
+        let url = Url::parse("https://yjapi.msa.buaa.edu.cn/api").unwrap();
+        let cookies = [
+            "a=1; Domain=buaa.edu.cn",
+            "b=2; Domain=.msa.buaa.edu.cn",
+            "c=3",
+            "d=4; Domain=bhpan.buaa.edu.cn",
+        ];
+        store.store_response_cookies(cookies.into_iter().map(Cookie::parse), &url);
+
+        assert_eq!(
+            ["a=1", "b=2", "c=3"],
+            *request(&store, "https://yjapi.msa.buaa.edu.cn/")
+        );
+        assert_eq!(
+            ["a=1", "b=2"],
+            *request(&store, "https://classroom.msa.buaa.edu.cn/")
+        );
+        assert_eq!(["a=1"], *request(&store, "https://bhpan.buaa.edu.cn/"));
+        assert!(request(&store, "https://notbuaa.edu.cn/").is_empty());
+    }
+
+    #[test]
+    fn test_cookie_expires() {
+        let mut store = CookieStore::default();
+        let url = Url::parse("https://sso.buaa.edu.cn/").unwrap();
+        let cookies = [
+            "a=1; Max-Age=3600",
+            "b=2; Expires=Wed, 21 Oct 2015 07:28:00 GMT",
+            "c=3; Expires=Fri, 01-Jan-2100 00:00:00 GMT",
+            "d=4; Max-Age=0; Expires=Fri, 01-Jan-2100 00:00:00 GMT",
+        ];
+        store.store_response_cookies(cookies.into_iter().map(Cookie::parse), &url);
+        assert_eq!(["a=1", "c=3"], *request(&store, "https://sso.buaa.edu.cn/"));
+        let c = store.get("sso.buaa.edu.cn", "c").unwrap();
+        assert_eq!(Some(4102444800), c.expires());
+
+        // 过期 Cookie 删除同名 Cookie
+        store.store_response_cookies(std::iter::once(Cookie::parse("a=; Max-Age=0")), &url);
+        assert_eq!(["c=3"], *request(&store, "https://sso.buaa.edu.cn/"));
+
+        // 有效期随 cookies.json 持久化
+        let json = serde_json::to_string(&store).unwrap();
+        let mut store: CookieStore = serde_json::from_str(&json).unwrap();
+        assert_eq!(["c=3"], *request(&store, "https://sso.buaa.edu.cn/"));
+        let expired = Cookie::parse("e=5; Expires=Thu, 01 Jan 1970 00:00:01 GMT");
+        assert!(expired.is_expired());
+        store.insert("sso.buaa.edu.cn", expired);
+        assert_eq!(1, store.purge_expired());
+        assert!(store.get("sso.buaa.edu.cn", "e").is_none());
+    }
+
+    #[test]
+    fn test_cookie_netscape() {
+        let txt = "# Netscape HTTP Cookie File\n\
+            .buaa.edu.cn\tTRUE\t/\tFALSE\t0\ta\t1\n\
+            #HttpOnly_sso.buaa.edu.cn\tFALSE\t/login\tTRUE\t4102444800\tb\t2\n";
+        let store = CookieStore::from_netscape(txt).unwrap();
+        assert_eq!(["a=1"], *request(&store, "https://byxt.buaa.edu.cn/"));
+        assert_eq!(
+            ["a=1", "b=2"],
+            *request(&store, "https://sso.buaa.edu.cn/login")
+        );
+        assert_eq!(
+            Some(4102444800),
+            store.get("sso.buaa.edu.cn", "b").unwrap().expires()
+        );
+
+        let store = CookieStore::from_netscape(&store.to_netscape()).unwrap();
+        assert_eq!(["a=1"], *request(&store, "http://sso.buaa.edu.cn/login"));
+        assert!(CookieStore::from_netscape("buaa.edu.cn\tTRUE\t/").is_err());
+    }
+
+    #[test]
+    fn test_cookie_browser_json() {
+        let json = r#"[
+            {"domain": ".buaa.edu.cn", "hostOnly": false, "path": "/", "secure": false,
+             "httpOnly": true, "session": true, "name": "a", "value": "1"},
+            {"domain": "sso.buaa.edu.cn", "path": "/", "secure": true,
+             "session": false, "expirationDate": 4102444800.5, "name": "b", "value": "2"}
+        ]"#;
+        let store = CookieStore::from_browser_json(json).unwrap();
+        assert_eq!(["a=1"], *request(&store, "https://byxt.buaa.edu.cn/"));
+        assert_eq!(["a=1", "b=2"], *request(&store, "https://sso.buaa.edu.cn/"));
+
+        let json = store.to_browser_json().unwrap();
+        let store = CookieStore::from_browser_json(&json).unwrap();
+        assert_eq!(
+            Some(4102444800),
+            store.get("sso.buaa.edu.cn", "b").unwrap().expires()
+        );
+        assert!(store.get("sso.buaa.edu.cn", "a").is_none());
+    }
+
+    #[test]
+    fn test_cred_encrypted() {
+        let cred = CredentialStore {
+            username: Some("username".to_string()),
+            password: Some("password".to_string()),
+            ..Default::default()
+        };
+
+        let data = cred.to_encrypted("passphrase").unwrap();
+        assert!(!data.windows(8).any(|w| w == b"password"));
+        let decrypted = CredentialStore::from_encrypted(&data, "passphrase").unwrap();
+        assert_eq!(Some("password"), decrypted.password.as_deref());
+
+        assert!(CredentialStore::from_encrypted(&data, "wrong").is_err());
+        let mut tampered = data.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(CredentialStore::from_encrypted(&tampered, "passphrase").is_err());
+
+        // 轮数不是固定值时直接拒绝, 不去派生密钥
+        let mut iterations = data.clone();
+        iterations[9..13].copy_from_slice(&u32::MAX.to_be_bytes());
+        let err = CredentialStore::from_encrypted(&iterations, "passphrase").unwrap_err();
+        assert!(matches!(err.kind(), crate::error::Kind::Parse));
+    }
+
+    #[test]
+    fn test_cred_file_storage_key() {
+        use crate::store::storage::{AuthStorage, FileStorage};
+
+        let dir = std::env::temp_dir().join(format!("buaa-cred-key-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let storage = FileStorage::new(&dir).passphrase("passphrase");
+        let cred = CredentialStore {
+            username: Some("username".to_string()),
+            ..Default::default()
+        };
+        let cookies = Default::default();
+
+        // 缓存的密钥复用同一个盐, 每次保存只换 IV
+        storage.save(&cookies, &cred).unwrap();
+        let first = std::fs::read(dir.join("cred.enc")).unwrap();
+        storage.save(&cookies, &cred).unwrap();
+        let second = std::fs::read(dir.join("cred.enc")).unwrap();
+        assert_eq!(first[13..29], second[13..29]);
+        assert_ne!(first[29..45], second[29..45]);
+
+        let (_, loaded) = FileStorage::new(&dir)
+            .passphrase("passphrase")
+            .load()
+            .unwrap();
+        assert_eq!(Some("username"), loaded.username.as_deref());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_cred_held_snapshot() {
+        use crate::api::Boya;
+        use crate::cell::AtomicCell;
+
+        let cell = AtomicCell::new(CredentialStore::default());
+        // 登录流程跨 await 持有快照, 期间其他任务改了账号
+        let held = cell.load();
+        cell.update(|c| c.username = Some("username".to_string()));
+        cell.update(|c| c.refresh::<Boya>());
+        assert!(held.is_expired::<Boya>());
+        assert!(!cell.load().is_expired::<Boya>());
+
+        let held = cell.load();
+        cell.update(|c| c.password = Some("password".to_string()));
+        cell.update(|c| c.expire::<Boya>());
+        drop(held);
+        let cred = cell.load();
+        assert!(cred.is_expired::<Boya>());
+        assert_eq!(Some("username"), cred.username.as_deref());
+    }
+}
@@ -26,3 +26,27 @@ pub fn parse_by_tag<'a>(bytes: &'a [u8], left: &str, right: &str) -> Option<&'a
     let end = rest.windows(right.len()).position(|w| w == right)?;
     std::str::from_utf8(&rest[..end]).ok()
 }
+
+// 对 URL 参数做百分号编码, 与 JS 的 encodeURIComponent 一致
+// 用于拼接 CAS 登录的 service 参数
+pub fn url_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() * 3);
+    for &b in s.as_bytes() {
+        match b {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'_'
+            | b'.'
+            | b'!'
+            | b'~'
+            | b'*'
+            | b'\''
+            | b'('
+            | b')' => out.push(b as char),
+            _ => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}